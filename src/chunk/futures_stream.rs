@@ -0,0 +1,511 @@
+use super::data_chunk::{
+    complete_utf8, incomplete_utf8, Boundary, Chunk, ChunkMetadata, ChunkSize, ChunkStrategy,
+    FileInfo, Progress, ProgressTracker,
+};
+use super::poll::PendingChunk;
+use super::{Memory, MemoryProvider};
+
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+pub use futures_core::Stream;
+use futures_io::{AsyncRead, AsyncSeek};
+
+/// Maximum size of a single read, the part of the buffer being read into is zeroed once before the first read into it
+const READ_STEP: usize = 1024 * 1024;
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+struct FilePack<R>
+where
+    R: AsyncRead + Unpin,
+{
+    metadata: FileInfo,
+    reader: R,
+    read_complete: bool,
+    /// Data that has already been read but belongs to the next chunk
+    tail: Vec<u8>,
+    /// The chunk that is being read, kept between polls
+    pending: Option<PendingChunk>,
+    /// Number of bytes past the end of the pending buffer that are already zeroed by previous reads
+    initialized: usize,
+}
+
+impl<R: AsyncRead + Unpin> FilePack<R> {
+    fn with_size(reader: R, size: Option<f64>, start_position: usize) -> FilePack<R> {
+        FilePack {
+            metadata: FileInfo {
+                size,
+                ..FileInfo::new(0.0, start_position)
+            },
+            reader,
+            read_complete: false,
+            tail: Vec::new(),
+            pending: None,
+            initialized: 0,
+        }
+    }
+
+    /// Discards the data that was read for the previous position, called when the position changes
+    fn discard_read_ahead(&mut self) {
+        self.tail.clear();
+        self.pending = None;
+    }
+
+    /// Polls the reading of the next chunk in place, the progress is kept in `pending` between polls
+    fn poll_read_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+        ram_available: f64,
+    ) -> Poll<io::Result<Chunk>> {
+        let mut pending = self.pending.take().unwrap_or_else(|| {
+            self.initialized = 0;
+            PendingChunk::new(
+                std::mem::take(&mut self.tail),
+                self.metadata.chunk_info.chunk_size.max(1.0) as usize,
+                ram_available,
+            )
+        });
+        let (reader, initialized) = (&mut self.reader, &mut self.initialized);
+        match pending.poll_end(
+            cx,
+            self.metadata.chunk_info.mode,
+            self.metadata.boundary.as_ref(),
+            |cx, buffer, max| poll_read_more(reader, cx, buffer, initialized, max),
+        ) {
+            Poll::Ready(Ok(end)) => {
+                let (chunk, tail) = pending.finish(end, &mut self.metadata);
+                self.tail = tail;
+                if chunk.value.is_empty() {
+                    self.read_complete = true;
+                }
+                Poll::Ready(Ok(chunk))
+            }
+            // nothing is lost, the next poll continues reading the same chunk
+            Poll::Ready(Err(e)) => {
+                self.pending = Some(pending);
+                Poll::Ready(Err(e))
+            }
+            Poll::Pending => {
+                self.pending = Some(pending);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Polls a single read of at most `max` bytes, appending them to `buffer`. `0` means there is no more data.
+///
+/// `initialized` is the number of bytes past the end of `buffer` that were zeroed by previous calls,
+/// only the rest of the space being read into is zeroed (as `ReadBuf` does in `tokio`).
+fn poll_read_more<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    cx: &mut Context<'_>,
+    buffer: &mut Vec<u8>,
+    initialized: &mut usize,
+    max: usize,
+) -> Poll<io::Result<usize>> {
+    let len = buffer.len();
+    let step = max.min(READ_STEP);
+    // SAFETY: the bytes were initialized by previous calls and the buffer has only been truncated since,
+    // which keeps its allocation
+    unsafe { buffer.set_len(len + *initialized) };
+    if *initialized < step {
+        buffer.resize(len + step, 0);
+    }
+    let spare = buffer.len() - len;
+    let read = Pin::new(reader).poll_read(cx, &mut buffer[len..len + step]);
+    let read_len = match read {
+        Poll::Ready(Ok(read)) => read.min(step),
+        _ => 0,
+    };
+    buffer.truncate(len + read_len);
+    *initialized = spare - read_len;
+    read
+}
+
+/// Moves `reader` to `position`, returns the new position
+async fn seek<R: AsyncSeek + Unpin>(reader: &mut R, position: io::SeekFrom) -> io::Result<u64> {
+    std::future::poll_fn(|cx| Pin::new(&mut *reader).poll_seek(cx, position)).await
+}
+
+/// The `FileStream` provides an asynchronous stream designed to read data chunks from any source
+/// implementing the `futures::io` traits, independent of the async runtime.
+///
+/// It operates in two modes:
+/// 1. **[`Auto Mode`](super::data_chunk::ChunkSize::Auto) (default):** Dynamically determines an optimal chunk size based on the previous read time,
+///    adjusting it relative to the available RAM (85% available per iteration, i.e.,
+///    if a chunk is too big and the system cannot process it, it is cut down to 85%.).
+/// 2. **[`Fixed Size Mode`](super::data_chunk::ChunkSize):** Allows users to manually set the chunk size, with any remaining data carried over
+///    to the next iteration as a single chunk.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct FileStream<R>
+where
+    R: AsyncRead + Unpin,
+{
+    memory: Memory,
+    file: FilePack<R>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> FileStream<R> {
+    /// Creates a new `FileStream` from any source that implements [`AsyncRead`] and [`AsyncSeek`]
+    /// (`async_std::fs::File`, `smol::fs::File`, `futures::io::Cursor`, etc.).
+    ///
+    /// The size of the source is probed by seeking to its end, after which the reader is returned
+    /// to its current position, so reading starts from there.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    ///
+    /// ### Errors
+    /// Returns an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if one of the seek operations fails.
+    ///
+    /// ## Example
+    /// ```
+    /// use futures::StreamExt;
+    /// use get_chunk::futures_stream::FileStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     smol::block_on(async {
+    ///         let mut file_stream = FileStream::from_reader(smol::fs::File::open("file.txt").await?).await?;
+    ///         while let Some(chunk) = file_stream.next().await {
+    ///             // ...
+    ///         }
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn from_reader(mut reader: R) -> io::Result<FileStream<R>> {
+        let start_position = seek(&mut reader, io::SeekFrom::Current(0)).await?;
+        let size = seek(&mut reader, io::SeekFrom::End(0)).await?;
+        seek(&mut reader, io::SeekFrom::Start(start_position)).await?;
+        Ok(FileStream {
+            memory: Memory::new(),
+            file: FilePack::with_size(reader, Some(size as f64), start_position as usize),
+        })
+    }
+
+    /// Creates a new `FileStream` from any source that implements [`AsyncRead`] and [`AsyncSeek`], with an explicitly specified size.
+    ///
+    /// Useful when the size is already known or seeking to the end of the source is expensive.
+    /// The reader is expected to be at its start.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    /// - `size`: The size of the source in bytes.
+    pub fn from_reader_with_size(reader: R, size: usize) -> FileStream<R> {
+        FileStream {
+            memory: Memory::new(),
+            file: FilePack::with_size(reader, Some(size as f64), 0),
+        }
+    }
+
+    /// Sets the start position for reading the file in bytes.
+    ///
+    /// ### Arguments
+    /// - `position`: The start position in bytes.
+    ///
+    /// ### Errors
+    /// Returns an [`io::Result`](https://doc.rust-lang.org/std/io/type.Result.html) indicating success or an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if the seek operation fails.
+    pub async fn set_start_position_bytes(mut self, position: usize) -> io::Result<Self> {
+        self.file.metadata.start_position = self
+            .file
+            .metadata
+            .size
+            .map_or(position, |size| position.min(size as usize));
+        self.file.metadata.position = self.file.metadata.start_position;
+        self.file.discard_read_ahead();
+        seek(
+            &mut self.file.reader,
+            io::SeekFrom::Start(self.file.metadata.start_position as u64),
+        )
+        .await?;
+        Ok(self)
+    }
+
+    /// Sets the start position for reading the file as a percentage of the total file size.
+    ///
+    /// ### Arguments
+    /// - `position_percent`: The start position as a percentage of the total file size.
+    ///
+    /// ### Errors
+    /// Returns an [`io::Result`](https://doc.rust-lang.org/std/io/type.Result.html) indicating success or an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if the seek operation fails.
+    pub async fn set_start_position_percent(mut self, position_percent: f64) -> io::Result<Self> {
+        self.file.metadata.start_position = (self.file.metadata.size.unwrap_or_default()
+            * (position_percent.clamp(0.0, 100.0) / 100.0))
+            as usize;
+        self.file.metadata.position = self.file.metadata.start_position;
+        self.file.discard_read_ahead();
+        seek(
+            &mut self.file.reader,
+            io::SeekFrom::Start(self.file.metadata.start_position as u64),
+        )
+        .await?;
+        Ok(self)
+    }
+}
+
+impl<R: AsyncRead + Unpin> FileStream<R> {
+    /// Creates a new `FileStream` from a source of unknown size that cannot seek, such as
+    /// a pipe of a child process or a socket.
+    ///
+    /// Since the size is unknown, the [`Auto`](crate::ChunkSize::Auto) mode starts from 64 KiB chunks
+    /// and the [`Percent`](crate::ChunkSize::Percent) mode always uses 64 KiB chunks (both still limited by RAM).
+    /// [`get_file_size`](FileStream::get_file_size) returns `0.0`.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    pub fn from_reader_without_size(reader: R) -> FileStream<R> {
+        FileStream {
+            memory: Memory::new(),
+            file: FilePack::with_size(reader, None, 0),
+        }
+    }
+
+    /// Checks if the read operation is complete, returning `true` if the data buffer is empty.
+    ///
+    /// ---
+    /// **⚠️ Warning**\
+    /// This method does not guarantee that the entire file has been read. If the contents
+    /// of the file are modified or deleted during iterations, this method may still return `true`.
+    pub fn is_read_complete(&self) -> bool {
+        self.file.read_complete
+    }
+
+    /// Returns the size of the file in bytes, `0.0` if the size of the source is unknown.
+    ///
+    /// ---
+    /// Use [`data_size_format`](crate::data_size_format) for comfortable reading and for calculating size
+    pub fn get_file_size(&self) -> f64 {
+        self.file.metadata.size.unwrap_or_default()
+    }
+
+    /// Defines the mode of dividing the file into chunks, automatic mode or fixed size
+    ///
+    /// ### Arguments
+    /// - [`mode`](crate::ChunkSize): The processing mode to be set.
+    pub fn set_mode(mut self, mode: ChunkSize) -> Self {
+        self.file.metadata.chunk_info.mode = mode;
+        self
+    }
+
+    /// Replaces the [strategy](crate::ChunkStrategy) that determines the chunk size in the [`Auto`](crate::ChunkSize::Auto) mode,
+    /// [`AutoStrategy`](crate::AutoStrategy) by default.
+    ///
+    /// ### Arguments
+    /// - `strategy`: The strategy to be set.
+    pub fn set_strategy<S: ChunkStrategy + 'static>(mut self, strategy: S) -> Self {
+        self.file.metadata.chunk_info.strategy = Box::new(strategy);
+        self
+    }
+
+    /// Sets the lower bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// The RAM limit (85% of the available memory) still takes precedence.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The minimum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_min_chunk(mut self, bytes: usize) -> Self {
+        self.file.metadata.chunk_info.min_chunk = bytes;
+        self
+    }
+
+    /// Sets the upper bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// If it is lower than the [minimum](Self::set_min_chunk), the minimum wins.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The maximum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_max_chunk(mut self, bytes: usize) -> Self {
+        self.file.metadata.chunk_info.max_chunk = bytes;
+        self
+    }
+
+    /// Calls `callback` with the [progress](crate::Progress) of the reading after every chunk:
+    /// bytes read, total size, percent, current and average read speed and the estimated time remaining.
+    ///
+    /// ### Arguments
+    /// - `callback`: The function that receives the progress.
+    pub fn on_progress<F: FnMut(Progress) + Send + 'static>(mut self, callback: F) -> Self {
+        self.file.metadata.progress = Some(ProgressTracker::new(callback));
+        self
+    }
+
+    /// Include the available SWAP (available `RAM` + available `SWAP`)
+    pub fn include_available_swap(mut self) -> Self {
+        self.memory.swap_check = true;
+        self
+    }
+
+    /// Replaces the [source](crate::MemoryProvider) of the available memory that limits the chunk size,
+    /// [`SystemMemory`](crate::SystemMemory) by default.
+    ///
+    /// ### Arguments
+    /// - `provider`: The memory provider to be set (e.g. [`FixedMemory`](crate::FixedMemory)).
+    pub fn set_memory_provider<P: MemoryProvider + 'static>(mut self, provider: P) -> Self {
+        self.memory.provider = Box::new(provider);
+        self
+    }
+
+    /// Never splits a record: each chunk is extended up to (and including) the next occurrence of `delimiter`.
+    ///
+    /// The chunk size is still determined by the selected [`mode`](crate::ChunkSize), the delimiter only
+    /// moves the end of the chunk forward. Data read past the delimiter is carried over to the next iteration.
+    /// If the delimiter is not found within 85% of the available RAM, the chunk is cut at this limit.
+    /// An empty `delimiter` disables the behavior.
+    ///
+    /// ### Arguments
+    /// - `delimiter`: The byte sequence that terminates a record (e.g. `b"\n"`).
+    pub fn set_delimiter<D: Into<Vec<u8>>>(mut self, delimiter: D) -> Self {
+        self.file.metadata.boundary = Some(delimiter.into())
+            .filter(|d| !d.is_empty())
+            .map(Boundary::Delimiter);
+        self
+    }
+
+    /// Yields only complete lines: each chunk is trimmed back to its last `\n` (so `\r\n` endings stay intact),
+    /// and the rest is carried over to the next iteration.
+    ///
+    /// The chunk size is still determined by the selected [`mode`](crate::ChunkSize). If a chunk does not contain
+    /// a single `\n`, it is extended up to the end of the line, limited to 85% of the available RAM.
+    /// The last line of the file is returned even if it has no line ending.
+    pub fn set_line_mode(mut self) -> Self {
+        self.file.metadata.boundary = Some(Boundary::Lines);
+        self
+    }
+
+    /// Converts the stream into [`Utf8FileStream`], which yields `String` chunks.
+    ///
+    /// Every chunk ends on a UTF-8 character boundary: an incomplete multibyte character at the end of
+    /// a chunk is carried over to the next one. Invalid UTF-8 data results in an
+    /// [`InvalidData`](io::ErrorKind::InvalidData) error.
+    pub fn utf8(self) -> Utf8FileStream<R> {
+        Utf8FileStream {
+            stream: self,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Converts the stream into [`MetadataFileStream`], which yields every chunk together with
+    /// its [metadata](crate::ChunkMetadata): offset in the file, index, mode and read speed.
+    pub fn with_metadata(self) -> MetadataFileStream<R> {
+        MetadataFileStream { stream: self }
+    }
+
+    /// Determines the size of the next chunk and polls its reading, `None` if there is no more data
+    fn poll_next_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Chunk>>> {
+        // the size is determined once per chunk, before its reading starts
+        if self.file.pending.is_none() {
+            if let Some(progress) = self.file.metadata.progress.as_mut() {
+                progress.start();
+            }
+            self.file.metadata.chunk_info.chunk_size = ChunkSize::calculate_chunk(
+                self.file.metadata.chunk_info.chunk_size,
+                self.file.metadata.chunk_info.bytes_per_second,
+                self.file.metadata.size,
+                {
+                    self.memory.update_ram();
+                    self.memory.ram_available
+                },
+                &mut self.file.metadata.chunk_info,
+            );
+        }
+        match ready!(self.file.poll_read_chunk(cx, self.memory.ram_available)) {
+            Ok(chunk) => {
+                self.file.metadata.chunk_info.bytes_per_second = chunk.bytes_per_second;
+                if let Some(progress) = self.file.metadata.progress.as_mut() {
+                    if !chunk.value.is_empty() {
+                        progress.update(
+                            chunk.value.len(),
+                            chunk.bytes_per_second,
+                            self.file.metadata.size,
+                            self.file.metadata.position,
+                        );
+                    }
+                }
+                if !chunk.value.is_empty() {
+                    Poll::Ready(Some(Ok(chunk)))
+                } else {
+                    Poll::Ready(None)
+                }
+            }
+            Err(e) => Poll::Ready(Some(Err(e))),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for FileStream<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .poll_next_chunk(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(|chunk| chunk.value)))
+    }
+}
+
+/// Stream adapter that yields [`ChunkMetadata`] instead of raw bytes.
+///
+/// Created by [`FileStream::with_metadata`].
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct MetadataFileStream<R>
+where
+    R: AsyncRead + Unpin,
+{
+    stream: FileStream<R>,
+}
+
+impl<R: AsyncRead + Unpin> Stream for MetadataFileStream<R> {
+    type Item = io::Result<ChunkMetadata>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let stream = &mut self.get_mut().stream;
+        let mode = stream.file.metadata.chunk_info.mode;
+        stream.poll_next_chunk(cx).map(|chunk| {
+            chunk.map(|chunk| {
+                chunk.map(|chunk| ChunkMetadata {
+                    value: chunk.value,
+                    offset: chunk.offset,
+                    index: chunk.index,
+                    mode,
+                    bytes_per_second: chunk.bytes_per_second,
+                })
+            })
+        })
+    }
+}
+
+/// Stream adapter that yields chunks as `String`, each chunk ends on a UTF-8 character boundary.
+///
+/// Created by [`FileStream::utf8`].
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Utf8FileStream<R>
+where
+    R: AsyncRead + Unpin,
+{
+    stream: FileStream<R>,
+    /// Bytes of the character that was split by the last chunk
+    pending: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> Stream for Utf8FileStream<R> {
+    type Item = io::Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(data))) => {
+                    if let Some(text) = complete_utf8(&mut this.pending, data) {
+                        return Poll::Ready(Some(text));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) if this.pending.is_empty() => return Poll::Ready(None),
+                Poll::Ready(None) => {
+                    this.pending.clear();
+                    return Poll::Ready(Some(Err(incomplete_utf8())));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -1,7 +1,13 @@
-use super::data_chunk::{Chunk, ChunkSize, FileInfo};
-use super::Memory;
+use super::data_chunk::{
+    complete_utf8, find_delimiter, incomplete_utf8, Boundary, Chunk, ChunkMetadata, ChunkSize,
+    ChunkStrategy, FileInfo, Progress, ProgressTracker, RateLimit, DELIMITER_SEARCH_STEP,
+};
+use super::{Memory, MemoryProvider};
 
 use std::io::Seek;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
 use std::time::Instant;
 
 use std::{
@@ -12,11 +18,13 @@ use std::{
 #[cfg_attr(feature = "debug", derive(Debug))]
 struct FilePack<R>
 where
-    R: Read + Seek,
+    R: Read,
 {
     metadata: FileInfo,
     buffer: BufReader<R>,
     read_complete: bool,
+    /// Data that has already been read but belongs to the next chunk
+    tail: Vec<u8>,
 }
 
 impl FilePack<File> {
@@ -25,6 +33,7 @@ impl FilePack<File> {
             metadata: FileInfo::new(buffer.get_ref().metadata()?.len() as f64, start_position),
             buffer,
             read_complete: false,
+            tail: Vec::new(),
         })
     }
 
@@ -42,6 +51,7 @@ impl FilePack<io::Cursor<Vec<u8>>> {
             metadata: FileInfo::new(buffer.get_ref().get_ref().len() as f64, start_position),
             buffer,
             read_complete: false,
+            tail: Vec::new(),
         })
     }
 
@@ -50,33 +60,145 @@ impl FilePack<io::Cursor<Vec<u8>>> {
     }
 }
 
-impl<R: Read + Seek> FilePack<R> {
-    fn read_chunk(&mut self) -> io::Result<Chunk> {
-        let mut buffer = Vec::new();
-        let timer = Instant::now();
+impl<R: Read> FilePack<R> {
+    fn with_size(buffer: BufReader<R>, size: Option<f64>, start_position: usize) -> FilePack<R> {
+        FilePack {
+            metadata: FileInfo {
+                size,
+                ..FileInfo::new(0.0, start_position)
+            },
+            buffer,
+            read_complete: false,
+            tail: Vec::new(),
+        }
+    }
+
+    /// Discards the data that was read for the previous position, called when the position changes
+    fn discard_read_ahead(&mut self) {
+        self.tail.clear();
+    }
 
+    /// Reads the chunk up to its size and then, if needed, until the boundary, the RAM limit is reached or the data runs out.
+    ///
+    /// Returns the length of the chunk, anything past it belongs to the next chunk.
+    fn read_end(
+        &mut self,
+        buffer: &mut Vec<u8>,
+        chunk_size: usize,
+        ram_available: f64,
+    ) -> io::Result<usize> {
         self.buffer
             .get_mut()
-            .take(self.metadata.chunk_info.prev_bytes_per_second.max(1.0) as u64)
-            .read_to_end(&mut buffer)?;
+            .take(chunk_size.saturating_sub(buffer.len()) as u64)
+            .read_to_end(buffer)?;
+
+        // the chunk may be moved to a boundary, but never beyond the RAM limit
+        let limit = (ChunkSize::max_chunk(ram_available) as usize).max(chunk_size);
+        let end = match (
+            self.metadata.chunk_info.mode,
+            self.metadata.boundary.as_ref(),
+        ) {
+            // the end of the chunk depends only on its content
+            (ChunkSize::ContentDefined { min, avg, .. }, _) => {
+                ChunkSize::content_defined_cut(buffer, min, avg)
+            }
+            (_, Some(Boundary::Delimiter(delimiter))) => read_until_delimiter(
+                self.buffer.get_mut(),
+                buffer,
+                delimiter,
+                chunk_size.saturating_sub(delimiter.len()),
+                limit,
+            )?,
+            // a shorter read means the end of the file, the last line is returned as is
+            (_, Some(Boundary::Lines)) if buffer.len() >= chunk_size => {
+                match buffer[..chunk_size].iter().rposition(|byte| *byte == b'\n') {
+                    Some(index) => index + 1,
+                    None => read_until_delimiter(
+                        self.buffer.get_mut(),
+                        buffer,
+                        b"\n",
+                        chunk_size,
+                        limit,
+                    )?,
+                }
+            }
+            _ => buffer.len(),
+        };
+        Ok(end)
+    }
+
+    /// Reads the next chunk into `buffer`, the previous contents of the buffer are discarded but its capacity is reused
+    fn read_chunk(&mut self, ram_available: f64, mut buffer: Vec<u8>) -> io::Result<Chunk> {
+        buffer.clear();
+        buffer.append(&mut self.tail);
+        let carried = buffer.len();
+        let chunk_size = self.metadata.chunk_info.chunk_size.max(1.0) as usize;
+        let timer = Instant::now();
+
+        let end = match self.read_end(&mut buffer, chunk_size, ram_available) {
+            Ok(end) => end,
+            Err(e) => {
+                // nothing is lost, the data read so far is carried over to the next attempt
+                self.tail = buffer;
+                return Err(e);
+            }
+        };
+        self.tail.extend_from_slice(&buffer[end..]);
+        buffer.truncate(end);
 
         let timer = timer.elapsed();
 
+        let offset = self.metadata.position;
+        let index = self.metadata.chunk_index;
         if buffer.is_empty() {
             self.read_complete = true;
+        } else {
+            self.metadata.position += buffer.len();
+            self.metadata.chunk_index += 1;
         }
 
         Ok(Chunk {
             bytes_per_second: if !timer.is_zero() {
-                buffer.len() as f64 / timer.as_secs_f64()
+                (buffer.len() + self.tail.len()).saturating_sub(carried) as f64
+                    / timer.as_secs_f64()
             } else {
-                self.metadata.chunk_info.prev_bytes_per_second
+                0.0
             },
             value: buffer,
+            offset,
+            index,
         })
     }
 }
 
+/// Reads from `reader` until `delimiter` is found at or after the `from` index, the `limit` is reached or the data runs out.
+///
+/// Returns the length of the chunk, anything past it belongs to the next chunk.
+fn read_until_delimiter<R: Read>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+    delimiter: &[u8],
+    mut from: usize,
+    limit: usize,
+) -> io::Result<usize> {
+    loop {
+        if let Some(end) = find_delimiter(buffer, delimiter, from) {
+            return Ok(end);
+        }
+        if buffer.len() >= limit {
+            return Ok(limit);
+        }
+        from = from.max(buffer.len().saturating_sub(delimiter.len() - 1));
+        if reader
+            .take((limit - buffer.len()).min(DELIMITER_SEARCH_STEP) as u64)
+            .read_to_end(buffer)?
+            == 0
+        {
+            return Ok(buffer.len());
+        }
+    }
+}
+
 /// The `FileIter` provides a synchronous file iterator designed to read data chunks from a file.
 ///
 /// It operates in two modes:
@@ -85,9 +207,8 @@ impl<R: Read + Seek> FilePack<R> {
 ///    if a chunk is too big and the system cannot process it, it is cut down to 85%.).
 /// 2. **[`Fixed Size Mode`](super::data_chunk::ChunkSize):** Allows users to manually set the chunk size, with any remaining data carried over
 ///    to the next iteration as a single chunk.
-
 #[cfg_attr(feature = "debug", derive(Debug))]
-pub struct FileIter<R: Seek + Read> {
+pub struct FileIter<R: Read> {
     memory: Memory,
     file: FilePack<R>,
 }
@@ -151,32 +272,113 @@ impl FileIter<File> {
     }
 }
 
-impl<R: Seek + Read> FileIter<R> {
-    /// Checks if the read operation is complete, returning `true` if the data buffer is empty.
+#[cfg(all(feature = "rayon", any(unix, windows)))]
+impl FileIter<File> {
+    /// Splits the rest of the file into byte ranges that are read in parallel by the [rayon](https://docs.rs/rayon) workers,
+    /// each item is the offset of the range in the file and its data.
     ///
-    /// ---
-    /// **⚠️ Warning**\
-    /// This method does not guarantee that the entire file has been read. If the contents
-    /// of the file are modified or deleted during iterations, this method may still return `true`.
-    pub fn is_read_complete(&self) -> bool {
-        self.file.read_complete
+    /// The size of the ranges is determined once by the selected [`mode`](crate::ChunkSize), as for the first chunk,
+    /// and is limited so that the ranges read by all the workers at once fit into 85% of the available RAM.
+    /// The workers read their ranges with positional reads, so they do not share a file position.
+    /// Delimiters, the line mode and content-defined cuts are not applied to the ranges.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::iterator::FileIter;
+    /// use rayon::prelude::*;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let sizes = FileIter::new("file.bin")?
+    ///         .par_chunks()
+    ///         .map(|range| range.map(|(_, data)| data.len()))
+    ///         .collect::<std::io::Result<Vec<_>>>()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    pub fn par_chunks(
+        mut self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = io::Result<(usize, Vec<u8>)>> {
+        use rayon::prelude::*;
+
+        self.memory.update_ram();
+        let size = self.file.metadata.size.unwrap_or_default() as usize;
+        let start = self.file.metadata.position.min(size);
+        let chunk_size = ChunkSize::calculate_chunk(
+            0.0,
+            0.0,
+            self.file.metadata.size,
+            self.memory.ram_available,
+            &mut self.file.metadata.chunk_info,
+        )
+        .min(ChunkSize::max_chunk(self.memory.ram_available) / rayon::current_num_threads() as f64)
+        .max(1.0) as usize;
+        let file = self.file.buffer.into_inner();
+
+        (0..(size - start).div_ceil(chunk_size))
+            .into_par_iter()
+            .map(move |index| {
+                let offset = start + index * chunk_size;
+                let mut buffer = vec![0; chunk_size.min(size - offset)];
+                super::read_exact_at(&file, &mut buffer, offset as u64)?;
+                Ok((offset, buffer))
+            })
     }
+}
 
-    /// Returns the size of the file in bytes.
+impl<R: Seek + Read> FileIter<R> {
+    /// Creates a new `FileIter` from any source that implements [`Read`] and [`Seek`]
+    /// (a virtual file, an archive entry, `Cursor<&[u8]>`, etc.), without copying the data.
     ///
-    /// ---
-    /// Use [`data_size_format`](crate::data_size_format) for comfortable reading and for calculating size
-    pub fn get_file_size(&self) -> f64 {
-        self.file.metadata.size
+    /// The size of the source is probed by seeking to its end, after which the reader is returned
+    /// to its current position, so reading starts from there.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    ///
+    /// ### Errors
+    /// Returns an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if one of the seek operations fails.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::iterator::FileIter;
+    /// use std::io::Cursor;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let data = b"some data without cloning";
+    ///     for chunk in FileIter::from_reader(Cursor::new(data.as_slice()))? {
+    ///         // ...
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_reader(mut reader: R) -> io::Result<FileIter<R>> {
+        let start_position = reader.stream_position()?;
+        let size = reader.seek(io::SeekFrom::End(0))?;
+        reader.seek(io::SeekFrom::Start(start_position))?;
+        Ok(FileIter {
+            memory: Memory::new(),
+            file: FilePack::with_size(
+                BufReader::new(reader),
+                Some(size as f64),
+                start_position as usize,
+            ),
+        })
     }
 
-    /// Defines the mode of dividing the file into chunks, automatic mode or fixed size
+    /// Creates a new `FileIter` from any source that implements [`Read`] and [`Seek`], with an explicitly specified size.
+    ///
+    /// Useful when the size is already known or seeking to the end of the source is expensive.
+    /// The reader is expected to be at its start.
     ///
     /// ### Arguments
-    /// - [`mode`](crate::ChunkSize): The processing mode to be set.
-    pub fn set_mode(mut self, mode: ChunkSize) -> Self {
-        self.file.metadata.chunk_info.mode = mode;
-        self
+    /// - `reader`: The source of the data.
+    /// - `size`: The size of the source in bytes.
+    pub fn from_reader_with_size(reader: R, size: usize) -> FileIter<R> {
+        FileIter {
+            memory: Memory::new(),
+            file: FilePack::with_size(BufReader::new(reader), Some(size as f64), 0),
+        }
     }
 
     /// Sets the start position for reading the file in bytes.
@@ -187,7 +389,13 @@ impl<R: Seek + Read> FileIter<R> {
     /// ### Errors
     /// Returns an [`io::Result`](https://doc.rust-lang.org/std/io/type.Result.html) indicating success or an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if the seek operation fails.
     pub fn set_start_position_bytes(mut self, position: usize) -> io::Result<Self> {
-        self.file.metadata.start_position = position.min(self.file.metadata.size as usize);
+        self.file.metadata.start_position = self
+            .file
+            .metadata
+            .size
+            .map_or(position, |size| position.min(size as usize));
+        self.file.metadata.position = self.file.metadata.start_position;
+        self.file.discard_read_ahead();
         self.file.buffer.seek(io::SeekFrom::Start(
             self.file.metadata.start_position as u64,
         ))?;
@@ -202,45 +410,432 @@ impl<R: Seek + Read> FileIter<R> {
     /// ### Errors
     /// Returns an [`io::Result`](https://doc.rust-lang.org/std/io/type.Result.html) indicating success or an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if the seek operation fails.
     pub fn set_start_position_percent(mut self, position_percent: f64) -> io::Result<Self> {
-        self.file.metadata.start_position =
-            (self.file.metadata.size * (position_percent / 100.0)).min(100.0) as usize;
+        self.file.metadata.start_position = (self.file.metadata.size.unwrap_or_default()
+            * (position_percent.clamp(0.0, 100.0) / 100.0))
+            as usize;
+        self.file.metadata.position = self.file.metadata.start_position;
+        self.file.discard_read_ahead();
         self.file.buffer.seek(io::SeekFrom::Start(
             self.file.metadata.start_position as u64,
         ))?;
         Ok(self)
     }
+}
+
+impl<R: Read> FileIter<R> {
+    /// Creates a new `FileIter` from a source of unknown size that cannot seek, such as
+    /// `stdin`, a pipe of a child process or a socket.
+    ///
+    /// Since the size is unknown, the [`Auto`](crate::ChunkSize::Auto) mode starts from 64 KiB chunks
+    /// and the [`Percent`](crate::ChunkSize::Percent) mode always uses 64 KiB chunks (both still limited by RAM).
+    /// [`get_file_size`](FileIter::get_file_size) returns `0.0`.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::iterator::FileIter;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     for chunk in FileIter::from_reader_without_size(std::io::stdin()) {
+    ///         // ...
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_reader_without_size(reader: R) -> FileIter<R> {
+        FileIter {
+            memory: Memory::new(),
+            file: FilePack::with_size(BufReader::new(reader), None, 0),
+        }
+    }
+
+    /// Checks if the read operation is complete, returning `true` if the data buffer is empty.
+    ///
+    /// ---
+    /// **⚠️ Warning**\
+    /// This method does not guarantee that the entire file has been read. If the contents
+    /// of the file are modified or deleted during iterations, this method may still return `true`.
+    pub fn is_read_complete(&self) -> bool {
+        self.file.read_complete
+    }
+
+    /// Returns the size of the file in bytes, `0.0` if the size of the source is unknown.
+    ///
+    /// ---
+    /// Use [`data_size_format`](crate::data_size_format) for comfortable reading and for calculating size
+    pub fn get_file_size(&self) -> f64 {
+        self.file.metadata.size.unwrap_or_default()
+    }
+
+    /// Defines the mode of dividing the file into chunks, automatic mode or fixed size
+    ///
+    /// ### Arguments
+    /// - [`mode`](crate::ChunkSize): The processing mode to be set.
+    pub fn set_mode(mut self, mode: ChunkSize) -> Self {
+        self.file.metadata.chunk_info.mode = mode;
+        self
+    }
+
+    /// Replaces the [strategy](crate::ChunkStrategy) that determines the chunk size in the [`Auto`](crate::ChunkSize::Auto) mode,
+    /// [`AutoStrategy`](crate::AutoStrategy) by default.
+    ///
+    /// ### Arguments
+    /// - `strategy`: The strategy to be set.
+    pub fn set_strategy<S: ChunkStrategy + 'static>(mut self, strategy: S) -> Self {
+        self.file.metadata.chunk_info.strategy = Box::new(strategy);
+        self
+    }
+
+    /// Sets the lower bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// The RAM limit (85% of the available memory) still takes precedence.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The minimum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_min_chunk(mut self, bytes: usize) -> Self {
+        self.file.metadata.chunk_info.min_chunk = bytes;
+        self
+    }
+
+    /// Sets the upper bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// If it is lower than the [minimum](Self::set_min_chunk), the minimum wins.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The maximum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_max_chunk(mut self, bytes: usize) -> Self {
+        self.file.metadata.chunk_info.max_chunk = bytes;
+        self
+    }
+
+    /// Limits the average read speed: the iterator blocks the current thread before reading the next chunk
+    /// until it is allowed (does not apply to [`par_chunks`](FileIter::par_chunks)).
+    /// The limit is applied between chunks, so a single chunk is read at full speed, and a pause in
+    /// the processing of the chunks does not allow faster reads afterwards. `0` removes the limit.
+    ///
+    /// ### Arguments
+    /// - `bytes_per_second`: The maximum read speed in bytes per second (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_rate_limit(mut self, bytes_per_second: usize) -> Self {
+        self.file.metadata.rate_limit = RateLimit::new(bytes_per_second);
+        self
+    }
+
+    /// Calls `callback` with the [progress](crate::Progress) of the reading after every chunk:
+    /// bytes read, total size, percent, current and average read speed and the estimated time remaining.
+    ///
+    /// ### Arguments
+    /// - `callback`: The function that receives the progress.
+    pub fn on_progress<F: FnMut(Progress) + Send + 'static>(mut self, callback: F) -> Self {
+        self.file.metadata.progress = Some(ProgressTracker::new(callback));
+        self
+    }
 
     /// Include the available SWAP (available `RAM` + available `SWAP`)
     pub fn include_available_swap(mut self) -> Self {
         self.memory.swap_check = true;
         self
     }
+
+    /// Replaces the [source](crate::MemoryProvider) of the available memory that limits the chunk size,
+    /// [`SystemMemory`](crate::SystemMemory) by default.
+    ///
+    /// ### Arguments
+    /// - `provider`: The memory provider to be set (e.g. [`FixedMemory`](crate::FixedMemory)).
+    pub fn set_memory_provider<P: MemoryProvider + 'static>(mut self, provider: P) -> Self {
+        self.memory.provider = Box::new(provider);
+        self
+    }
+
+    /// Never splits a record: each chunk is extended up to (and including) the next occurrence of `delimiter`.
+    ///
+    /// The chunk size is still determined by the selected [`mode`](crate::ChunkSize), the delimiter only
+    /// moves the end of the chunk forward. Data read past the delimiter is carried over to the next iteration.
+    /// If the delimiter is not found within 85% of the available RAM, the chunk is cut at this limit.
+    /// An empty `delimiter` disables the behavior.
+    ///
+    /// ### Arguments
+    /// - `delimiter`: The byte sequence that terminates a record (e.g. `b"\n"`).
+    pub fn set_delimiter<D: Into<Vec<u8>>>(mut self, delimiter: D) -> Self {
+        self.file.metadata.boundary = Some(delimiter.into())
+            .filter(|d| !d.is_empty())
+            .map(Boundary::Delimiter);
+        self
+    }
+
+    /// Yields only complete lines: each chunk is trimmed back to its last `\n` (so `\r\n` endings stay intact),
+    /// and the rest is carried over to the next iteration.
+    ///
+    /// The chunk size is still determined by the selected [`mode`](crate::ChunkSize). If a chunk does not contain
+    /// a single `\n`, it is extended up to the end of the line, limited to 85% of the available RAM.
+    /// The last line of the file is returned even if it has no line ending.
+    pub fn set_line_mode(mut self) -> Self {
+        self.file.metadata.boundary = Some(Boundary::Lines);
+        self
+    }
+
+    /// Converts the iterator into [`Utf8FileIter`], which yields `String` chunks.
+    ///
+    /// Every chunk ends on a UTF-8 character boundary: an incomplete multibyte character at the end of
+    /// a chunk is carried over to the next one. Invalid UTF-8 data results in an
+    /// [`InvalidData`](io::ErrorKind::InvalidData) error.
+    pub fn utf8(self) -> Utf8FileIter<R> {
+        Utf8FileIter {
+            iter: self,
+            pending: Vec::new(),
+        }
+    }
 }
 
-impl<R: Seek + Read> Iterator for FileIter<R> {
-    type Item = io::Result<Vec<u8>>;
+impl<R: Read> FileIter<R> {
+    /// Converts the iterator into [`MetadataFileIter`], which yields every chunk together with
+    /// its [metadata](crate::ChunkMetadata): offset in the file, index, mode and read speed.
+    pub fn with_metadata(self) -> MetadataFileIter<R> {
+        MetadataFileIter { iter: self }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.file.metadata.chunk_info.prev_bytes_per_second = ChunkSize::calculate_chunk(
-            self.file.metadata.chunk_info.prev_bytes_per_second,
-            self.file.metadata.chunk_info.now_bytes_per_second,
+    /// Reads the next chunk into `buffer`, reusing its allocation instead of allocating a new one for every chunk.
+    ///
+    /// The previous contents of the buffer are discarded. Returns the length of the chunk,
+    /// `0` if there is no more data.
+    ///
+    /// ### Arguments
+    /// - `buffer`: The buffer to read the chunk into.
+    ///
+    /// ### Errors
+    /// Returns an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if the read operation fails.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::iterator::FileIter;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file_iter = FileIter::new("file.txt")?;
+    ///     let mut buffer = Vec::new();
+    ///     while file_iter.read_into(&mut buffer)? != 0 {
+    ///         // some calculations with `buffer`
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn read_into(&mut self, buffer: &mut Vec<u8>) -> io::Result<usize> {
+        *buffer = self.read_next(std::mem::take(buffer))?.value;
+        Ok(buffer.len())
+    }
+
+    /// Determines the size of the next chunk and reads it into `buffer`, the chunk is empty if there is no more data
+    fn read_next(&mut self, buffer: Vec<u8>) -> io::Result<Chunk> {
+        if let Some(progress) = self.file.metadata.progress.as_mut() {
+            progress.start();
+        }
+        if let Some(rate_limit) = self.file.metadata.rate_limit.as_ref() {
+            thread::sleep(rate_limit.delay());
+        }
+        self.file.metadata.chunk_info.chunk_size = ChunkSize::calculate_chunk(
+            self.file.metadata.chunk_info.chunk_size,
+            self.file.metadata.chunk_info.bytes_per_second,
             self.file.metadata.size,
             {
                 self.memory.update_ram();
                 self.memory.ram_available
             },
-            self.file.metadata.chunk_info.mode,
+            &mut self.file.metadata.chunk_info,
         );
-        match self.file.read_chunk() {
-            Ok(chunk) => {
-                self.file.metadata.chunk_info.now_bytes_per_second = chunk.bytes_per_second;
-                if !chunk.value.is_empty() {
-                    Some(Ok(chunk.value))
-                } else {
-                    None
+        let chunk = self.file.read_chunk(self.memory.ram_available, buffer)?;
+        self.file.metadata.chunk_info.bytes_per_second = chunk.bytes_per_second;
+        if let Some(rate_limit) = self.file.metadata.rate_limit.as_mut() {
+            rate_limit.consume(chunk.value.len());
+        }
+        if let Some(progress) = self.file.metadata.progress.as_mut() {
+            if !chunk.value.is_empty() {
+                progress.update(
+                    chunk.value.len(),
+                    chunk.bytes_per_second,
+                    self.file.metadata.size,
+                    self.file.metadata.position,
+                );
+            }
+        }
+        Ok(chunk)
+    }
+
+    /// Determines the size of the next chunk and reads it, `None` if there is no more data
+    fn next_chunk(&mut self) -> Option<io::Result<Chunk>> {
+        match self.read_next(Vec::new()) {
+            Ok(chunk) if chunk.value.is_empty() => None,
+            result => Some(result),
+        }
+    }
+}
+
+impl<R: Read + Send + 'static> FileIter<R> {
+    /// Converts the iterator into [`PrefetchFileIter`], which keeps reading up to `depth` upcoming chunks
+    /// on a background thread while the current chunk is being processed.
+    ///
+    /// The prefetched chunks that have not been taken yet never occupy more than 85% of the available RAM
+    /// (the same limit that applies to a single chunk), the reading pauses until they are taken.
+    /// A `depth` of `0` is treated as `1`.
+    ///
+    /// ### Arguments
+    /// - `depth`: The maximum number of chunks read ahead.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::iterator::FileIter;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     for chunk in FileIter::new("file.txt")?.prefetch(2) {
+    ///         // the next chunks are read while this one is processed
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn prefetch(mut self, depth: usize) -> PrefetchFileIter {
+        // the thread holds one more chunk while it waits for `send`, a `depth` of `1` is a rendezvous channel
+        let (sender, receiver) = mpsc::sync_channel(depth.max(1) - 1);
+        let buffered = Arc::new(Buffered::default());
+        thread::spawn({
+            let buffered = Arc::clone(&buffered);
+            move || {
+                while let Some(chunk) = self.next_chunk() {
+                    let len = chunk.as_ref().map_or(0, |chunk| chunk.value.len());
+                    *buffered.lock_bytes() += len;
+                    if sender.send(chunk.map(|chunk| chunk.value)).is_err() {
+                        return;
+                    }
+                    // waits until the prefetched data fits into the RAM limit
+                    let mut bytes = buffered.lock_bytes();
+                    loop {
+                        if buffered.closed.load(Ordering::Acquire) {
+                            return;
+                        }
+                        self.memory.update_ram();
+                        if *bytes <= ChunkSize::max_chunk(self.memory.ram_available) as usize {
+                            break;
+                        }
+                        bytes = buffered
+                            .taken
+                            .wait(bytes)
+                            .unwrap_or_else(PoisonError::into_inner);
+                    }
+                }
+            }
+        });
+        PrefetchFileIter { receiver, buffered }
+    }
+}
+
+impl<R: Read> Iterator for FileIter<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk()
+            .map(|chunk| chunk.map(|chunk| chunk.value))
+    }
+}
+
+/// Iterator adapter that yields [`ChunkMetadata`] instead of raw bytes.
+///
+/// Created by [`FileIter::with_metadata`].
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct MetadataFileIter<R: Read> {
+    iter: FileIter<R>,
+}
+
+impl<R: Read> Iterator for MetadataFileIter<R> {
+    type Item = io::Result<ChunkMetadata>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mode = self.iter.file.metadata.chunk_info.mode;
+        self.iter.next_chunk().map(|chunk| {
+            chunk.map(|chunk| ChunkMetadata {
+                value: chunk.value,
+                offset: chunk.offset,
+                index: chunk.index,
+                mode,
+                bytes_per_second: chunk.bytes_per_second,
+            })
+        })
+    }
+}
+
+/// Chunks that have been prefetched but not taken yet
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Default)]
+struct Buffered {
+    /// Total size of the chunks in bytes
+    bytes: Mutex<usize>,
+    /// Notified every time a chunk is taken
+    taken: Condvar,
+    /// The receiving side has been dropped
+    closed: AtomicBool,
+}
+
+impl Buffered {
+    fn lock_bytes(&self) -> MutexGuard<'_, usize> {
+        self.bytes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Iterator adapter that reads upcoming chunks on a background thread, so reading and processing overlap.
+///
+/// Created by [`FileIter::prefetch`]. Dropping the iterator stops the background thread
+/// once its current read is finished.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct PrefetchFileIter {
+    receiver: mpsc::Receiver<io::Result<Vec<u8>>>,
+    buffered: Arc<Buffered>,
+}
+
+impl Iterator for PrefetchFileIter {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.receiver.recv().ok()?;
+        if let Ok(data) = chunk.as_ref() {
+            *self.buffered.lock_bytes() -= data.len();
+            self.buffered.taken.notify_one();
+        }
+        Some(chunk)
+    }
+}
+
+impl Drop for PrefetchFileIter {
+    fn drop(&mut self) {
+        // the lock makes sure the thread is either waiting or has not checked the flag yet
+        let _bytes = self.buffered.lock_bytes();
+        self.buffered.closed.store(true, Ordering::Release);
+        self.buffered.taken.notify_one();
+    }
+}
+
+/// Iterator adapter that yields chunks as `String`, each chunk ends on a UTF-8 character boundary.
+///
+/// Created by [`FileIter::utf8`].
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Utf8FileIter<R: Read> {
+    iter: FileIter<R>,
+    /// Bytes of the character that was split by the last chunk
+    pending: Vec<u8>,
+}
+
+impl<R: Read> Iterator for Utf8FileIter<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.iter.next() {
+                Some(Ok(data)) => {
+                    if let Some(text) = complete_utf8(&mut self.pending, data) {
+                        return Some(text);
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None if self.pending.is_empty() => return None,
+                None => {
+                    self.pending.clear();
+                    return Some(Err(incomplete_utf8()));
                 }
             }
-            Err(e) => Some(Err(e)),
         }
     }
 }
@@ -0,0 +1,241 @@
+use super::data_chunk::{ChunkSize, ChunkStrategy, FileInfo, Progress, ProgressTracker};
+use super::{Memory, MemoryProvider};
+
+use memmap2::Mmap;
+use std::time::Instant;
+use std::{fs::File, io};
+
+/// Pages are touched one byte at a time to load the chunk into memory (4 KiB is the most common page size)
+const PAGE_SIZE: usize = 4096;
+
+/// The `FileMmap` maps a file into memory and splits it into chunks that are borrowed slices of the mapping.
+///
+/// Unlike [`FileIter`](crate::iterator::FileIter), the data is not copied into a new `Vec<u8>` for each chunk.
+/// The size of the chunks (the window over the mapping) is determined in the same way, by the selected
+/// [`mode`](crate::ChunkSize) and the available RAM. The read speed used by the [`Auto`](crate::ChunkSize::Auto)
+/// mode is the speed of loading the pages of a chunk into memory.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct FileMmap {
+    memory: Memory,
+    map: Mmap,
+    metadata: FileInfo,
+}
+
+impl FileMmap {
+    /// Maps the file at `path` into memory. The default setting is automatic detection of the chunk size
+    ///
+    /// ### Arguments
+    /// * `path` - A path to the file.
+    ///
+    /// ### Safety
+    /// The file must not be modified or truncated (by this or another process) while it is mapped,
+    /// otherwise the chunks may change under the hands of the reader, which is undefined behavior.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::mmap::FileMmap;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     // SAFETY: the file is not modified while it is mapped
+    ///     let mut file_mmap = unsafe { FileMmap::new("file.bin")? };
+    ///     for chunk in file_mmap.chunks() {
+    ///         // `chunk` is a `&[u8]` slice of the mapping
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub unsafe fn new<S: Into<Box<str>>>(path: S) -> io::Result<FileMmap> {
+        FileMmap::from_file(&File::open(&*path.into())?)
+    }
+
+    /// Maps an already opened file into memory.
+    ///
+    /// ### Safety
+    /// The same requirements as for [`new`](FileMmap::new).
+    pub unsafe fn from_file(file: &File) -> io::Result<FileMmap> {
+        let map = Mmap::map(file)?;
+        Ok(FileMmap {
+            memory: Memory::new(),
+            metadata: FileInfo::new(map.len() as f64, 0),
+            map,
+        })
+    }
+
+    /// Checks if all chunks have been handed out.
+    pub fn is_read_complete(&self) -> bool {
+        self.metadata.position >= self.map.len()
+    }
+
+    /// Returns the size of the file in bytes.
+    ///
+    /// ---
+    /// Use [`data_size_format`](crate::data_size_format) for comfortable reading and for calculating size
+    pub fn get_file_size(&self) -> f64 {
+        self.map.len() as f64
+    }
+
+    /// Defines the mode of dividing the file into chunks, automatic mode or fixed size
+    ///
+    /// ### Arguments
+    /// - [`mode`](crate::ChunkSize): The processing mode to be set.
+    pub fn set_mode(mut self, mode: ChunkSize) -> Self {
+        self.metadata.chunk_info.mode = mode;
+        self
+    }
+
+    /// Replaces the [strategy](crate::ChunkStrategy) that determines the chunk size in the [`Auto`](crate::ChunkSize::Auto) mode,
+    /// [`AutoStrategy`](crate::AutoStrategy) by default.
+    ///
+    /// ### Arguments
+    /// - `strategy`: The strategy to be set.
+    pub fn set_strategy<S: ChunkStrategy + 'static>(mut self, strategy: S) -> Self {
+        self.metadata.chunk_info.strategy = Box::new(strategy);
+        self
+    }
+
+    /// Sets the lower bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// The RAM limit (85% of the available memory) still takes precedence.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The minimum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_min_chunk(mut self, bytes: usize) -> Self {
+        self.metadata.chunk_info.min_chunk = bytes;
+        self
+    }
+
+    /// Sets the upper bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// If it is lower than the [minimum](Self::set_min_chunk), the minimum wins.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The maximum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_max_chunk(mut self, bytes: usize) -> Self {
+        self.metadata.chunk_info.max_chunk = bytes;
+        self
+    }
+
+    /// Calls `callback` with the [progress](crate::Progress) of the reading after every chunk:
+    /// bytes handed out, total size, percent, current and average speed of loading the pages and the estimated time remaining.
+    ///
+    /// ### Arguments
+    /// - `callback`: The function that receives the progress.
+    pub fn on_progress<F: FnMut(Progress) + Send + 'static>(mut self, callback: F) -> Self {
+        self.metadata.progress = Some(ProgressTracker::new(callback));
+        self
+    }
+
+    /// Sets the start position for reading the file in bytes.
+    ///
+    /// ### Arguments
+    /// - `position`: The start position in bytes.
+    pub fn set_start_position_bytes(mut self, position: usize) -> Self {
+        self.metadata.start_position = position.min(self.map.len());
+        self.metadata.position = self.metadata.start_position;
+        self
+    }
+
+    /// Sets the start position for reading the file as a percentage of the total file size.
+    ///
+    /// ### Arguments
+    /// - `position_percent`: The start position as a percentage of the total file size.
+    pub fn set_start_position_percent(self, position_percent: f64) -> Self {
+        let position = self.map.len() as f64 * (position_percent.clamp(0.0, 100.0) / 100.0);
+        self.set_start_position_bytes(position as usize)
+    }
+
+    /// Include the available SWAP (available `RAM` + available `SWAP`)
+    pub fn include_available_swap(mut self) -> Self {
+        self.memory.swap_check = true;
+        self
+    }
+
+    /// Replaces the [source](crate::MemoryProvider) of the available memory that limits the chunk size,
+    /// [`SystemMemory`](crate::SystemMemory) by default.
+    ///
+    /// ### Arguments
+    /// - `provider`: The memory provider to be set (e.g. [`FixedMemory`](crate::FixedMemory)).
+    pub fn set_memory_provider<P: MemoryProvider + 'static>(mut self, provider: P) -> Self {
+        self.memory.provider = Box::new(provider);
+        self
+    }
+
+    /// Returns an iterator over the remaining chunks of the mapping.
+    ///
+    /// The iteration continues from the position where the previous iterator stopped.
+    pub fn chunks(&mut self) -> MmapChunks<'_> {
+        MmapChunks {
+            memory: &mut self.memory,
+            metadata: &mut self.metadata,
+            data: &self.map,
+        }
+    }
+}
+
+/// Iterator over the chunks of a [`FileMmap`], each chunk is a borrowed slice of the mapping.
+///
+/// Created by [`FileMmap::chunks`].
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct MmapChunks<'a> {
+    memory: &'a mut Memory,
+    metadata: &'a mut FileInfo,
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for MmapChunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(progress) = self.metadata.progress.as_mut() {
+            progress.start();
+        }
+        let chunk_info = &mut self.metadata.chunk_info;
+        chunk_info.chunk_size = ChunkSize::calculate_chunk(
+            chunk_info.chunk_size,
+            chunk_info.bytes_per_second,
+            self.metadata.size,
+            {
+                self.memory.update_ram();
+                self.memory.ram_available
+            },
+            chunk_info,
+        );
+
+        let data = self.data.get(self.metadata.position..).unwrap_or_default();
+        if data.is_empty() {
+            return None;
+        }
+        let mut chunk = &data[..(chunk_info.chunk_size.max(1.0) as usize).min(data.len())];
+        if let ChunkSize::ContentDefined { min, avg, .. } = chunk_info.mode {
+            chunk = &chunk[..ChunkSize::content_defined_cut(chunk, min, avg)];
+        }
+
+        let timer = Instant::now();
+        load_pages(chunk);
+        let timer = timer.elapsed();
+
+        chunk_info.bytes_per_second = if !timer.is_zero() {
+            chunk.len() as f64 / timer.as_secs_f64()
+        } else {
+            0.0
+        };
+        self.metadata.position += chunk.len();
+        self.metadata.chunk_index += 1;
+        if let Some(progress) = self.metadata.progress.as_mut() {
+            progress.update(
+                chunk.len(),
+                chunk_info.bytes_per_second,
+                self.metadata.size,
+                self.metadata.position,
+            );
+        }
+        Some(chunk)
+    }
+}
+
+/// Touches every page of `chunk`, so the OS loads it into memory
+fn load_pages(chunk: &[u8]) {
+    let checksum = chunk
+        .iter()
+        .step_by(PAGE_SIZE)
+        .fold(0u8, |acc, byte| acc ^ byte);
+    std::hint::black_box(checksum);
+}
@@ -1,5 +1,10 @@
+use std::fs;
+use std::path::{Path, PathBuf};
 use sysinfo::{MemoryRefreshKind, RefreshKind, System};
 
+/// Where the cgroup file system is mounted on Linux
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
 ///
 /// ## Version: Sync
 ///
@@ -20,55 +25,252 @@ pub mod iterator;
 #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
 pub mod stream;
 
+///
+/// ## Version: Async (runtime-agnostic)
+///
+/// This module defines the [FileStream](futures_stream::FileStream) struct, the counterpart of the
+/// tokio based `stream` module, built on the `futures::io` traits and `futures_core::Stream`,
+/// so it can be used with any async runtime (smol, async-std, etc.).
+///
+/// Without a runtime there is no timer to sleep on, so `set_rate_limit` of the tokio `FileStream` is not provided here.
+#[cfg(feature = "futures_stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures_stream")))]
+pub mod futures_stream;
+
+/// Reading of a chunk that is polled in place, shared by the async streams
+#[cfg(any(feature = "stream", feature = "futures_stream"))]
+mod poll;
+
+///
+/// ## Version: Memory-mapped
+///
+/// This module defines the [FileMmap](mmap::FileMmap) struct, which maps a file into memory and hands out
+/// chunks as borrowed slices of the mapping, without copying them. It is suitable for large read-only files.
+#[cfg(feature = "mmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+pub mod mmap;
+
+/// A source of the amount of memory that may be used for chunks.
+///
+/// The default implementation is [`SystemMemory`], a custom one can be set with `set_memory_provider`
+/// of a file iterator or stream, e.g. [`FixedMemory`] to limit the chunks to a fixed budget.
+/// 85% of the returned amount is available for a single chunk.
+/// The provider is `Send + Sync`, so the file iterators and streams that own it stay `Send + Sync`.
+pub trait MemoryProvider: Send + Sync {
+    /// Returns the amount of available memory in bytes, queried before every chunk.
+    ///
+    /// ### Arguments
+    /// - `include_swap`: Whether the available swap is included (see `include_available_swap`).
+    fn available_memory(&mut self, include_swap: bool) -> f64;
+}
+
+#[cfg(feature = "debug")]
+impl std::fmt::Debug for dyn MemoryProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MemoryProvider")
+    }
+}
+
+/// The default [`MemoryProvider`]: the available RAM (and swap) of the system, as reported by `sysinfo`.
+///
+/// On Linux the limit of the cgroup (v2 `memory.max` and `memory.current`, or v1 `memory.limit_in_bytes`
+/// and `memory.usage_in_bytes`) is also taken into account, so in a container the tighter of the two bounds is used.
+/// The inactive page cache (`inactive_file` in `memory.stat`, `total_inactive_file` in v1) is not counted as used,
+/// since the kernel reclaims it before the limit is hit.
 #[cfg_attr(feature = "debug", derive(Debug))]
-/// A structure that stores the information needed to determine the `optimal` chunk size
-pub struct Memory {
+pub struct SystemMemory {
     /// Here we store an object that can store various data about the system
     system_info: System,
-    /// Information about the free and total space in RAM.
-    ram_available: f64,
-    /// A flag that indicates whether to check the swap when calculating the chunk size.
-    swap_check: bool,
+    /// Where the cgroup file system is mounted, `None` if the cgroup limits are not checked
+    cgroup_root: Option<PathBuf>,
 }
 
-impl Memory {
-    fn new() -> Self {
+impl SystemMemory {
+    /// Creates a new `SystemMemory`
+    pub fn new() -> Self {
         // only RAM tracking
         Self {
-            ram_available: 0.0,
             system_info: System::new_with_specifics(
                 RefreshKind::new().with_memory(MemoryRefreshKind::new().with_ram()),
             ),
-            swap_check: false,
+            cgroup_root: cfg!(target_os = "linux").then(|| PathBuf::from(CGROUP_ROOT)),
         }
     }
 
-    fn update_ram(&mut self) {
+    /// Reads the cgroup limits from `root` instead of `/sys/fs/cgroup`
+    /// (e.g. the directory of a nested cgroup, or fake files in tests).
+    ///
+    /// ### Arguments
+    /// - `root`: The directory with the cgroup v2 files, or with the `memory` directory of cgroup v1.
+    pub fn with_cgroup_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.cgroup_root = Some(root.into());
+        self
+    }
+
+    /// Returns the memory left before the cgroup limit is reached, `None` if there is no limit
+    fn cgroup_available(&self) -> Option<f64> {
+        let root = self.cgroup_root.as_ref()?;
+        let (limit, usage) = read_cgroup(
+            &root.join("memory.max"),
+            &root.join("memory.current"),
+            (&root.join("memory.stat"), "inactive_file"),
+        )
+        .or_else(|| {
+            read_cgroup(
+                &root.join("memory/memory.limit_in_bytes"),
+                &root.join("memory/memory.usage_in_bytes"),
+                (&root.join("memory/memory.stat"), "total_inactive_file"),
+            )
+        })?;
+        Some(limit.saturating_sub(usage) as f64)
+    }
+}
+
+/// Reads the limit and the usage of a cgroup, `None` if a file is missing or the memory is unlimited (`max`).
+///
+/// The `inactive` entry of the stat file (if present) is subtracted from the usage.
+fn read_cgroup(limit: &Path, usage: &Path, (stat, inactive): (&Path, &str)) -> Option<(u64, u64)> {
+    let read = |path: &Path| fs::read_to_string(path).ok()?.trim().parse::<u64>().ok();
+    let stat = fs::read_to_string(stat).unwrap_or_default();
+    let inactive = stat
+        .lines()
+        .find_map(|line| line.strip_prefix(inactive)?.strip_prefix(' '))
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or_default();
+    Some((read(limit)?, read(usage)?.saturating_sub(inactive)))
+}
+
+impl Default for SystemMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryProvider for SystemMemory {
+    fn available_memory(&mut self, include_swap: bool) -> f64 {
         self.system_info
-            .refresh_memory_specifics(match self.swap_check {
+            .refresh_memory_specifics(match include_swap {
                 true => MemoryRefreshKind::new().with_ram().with_swap(),
                 false => MemoryRefreshKind::new().with_ram().without_swap(),
             });
-        self.ram_available = if self.swap_check {
+        let available = if include_swap {
             (self.system_info.available_memory() + self.system_info.free_swap()) as f64
         } else {
             self.system_info.available_memory() as f64
         };
+        self.cgroup_available()
+            .map_or(available, |cgroup| available.min(cgroup))
+    }
+}
+
+/// A [`MemoryProvider`] with a fixed budget in bytes, regardless of the state of the system (the swap is ignored).
+///
+/// ## Example
+/// ```
+/// use get_chunk::{iterator::FileIter, FixedMemory};
+///
+/// fn main() -> std::io::Result<()> {
+///     // chunks never exceed 85% of 512 MiB
+///     let file_iter = FileIter::new("file.bin")?.set_memory_provider(FixedMemory(512 * 1024 * 1024));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FixedMemory(pub usize);
+
+impl MemoryProvider for FixedMemory {
+    fn available_memory(&mut self, _: bool) -> f64 {
+        self.0 as f64
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+/// A structure that stores the information needed to determine the `optimal` chunk size
+pub struct Memory {
+    /// Where the amount of available memory comes from
+    provider: Box<dyn MemoryProvider>,
+    /// Information about the free and total space in RAM.
+    ram_available: f64,
+    /// A flag that indicates whether to check the swap when calculating the chunk size.
+    swap_check: bool,
+}
+
+impl Memory {
+    fn new() -> Self {
+        Self {
+            ram_available: 0.0,
+            provider: Box::new(SystemMemory::new()),
+            swap_check: false,
+        }
+    }
+
+    fn update_ram(&mut self) {
+        self.ram_available = self.provider.available_memory(self.swap_check);
+    }
+}
+
+/// Reads exactly `buffer.len()` bytes at `offset` without using the position of the file,
+/// so the same file can be read from several threads at once
+#[cfg(all(any(feature = "rayon", feature = "stream"), any(unix, windows)))]
+fn read_exact_at(file: &std::fs::File, buffer: &mut [u8], offset: u64) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::FileExt::read_exact_at(file, buffer, offset)
+    }
+    #[cfg(windows)]
+    {
+        let mut read = 0;
+        while read < buffer.len() {
+            match std::os::windows::fs::FileExt::seek_read(
+                file,
+                &mut buffer[read..],
+                offset + read as u64,
+            ) {
+                Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
     }
 }
 
 pub mod data_chunk {
+    use std::io;
+    use std::sync::{Mutex, PoisonError};
+    use std::time::{Duration, Instant};
 
     #[cfg_attr(feature = "debug", derive(Debug))]
+    pub struct Chunk<B = Vec<u8>> {
+        pub value: B,
+        pub bytes_per_second: f64,
+        pub offset: usize,
+        pub index: usize,
+    }
 
-    pub struct Chunk {
+    /// A chunk together with information about where and how it was read
+    #[cfg_attr(feature = "debug", derive(Debug))]
+    #[derive(Clone)]
+    pub struct ChunkMetadata {
+        /// The data of the chunk
         pub value: Vec<u8>,
+        /// Absolute position of the first byte of the chunk in the file
+        pub offset: usize,
+        /// Sequential number of the chunk, starting from `0`
+        pub index: usize,
+        /// The mode that was used to determine the size of the chunk
+        pub mode: ChunkSize,
+        /// Read speed of the chunk, measured in bytes per second (`0.0` if the read was too fast to be measured)
         pub bytes_per_second: f64,
     }
 
     /// The `ChunkSize` enum represents different modes for determining the chunk size in the file processing module.
     /// Regardless of the specific mode chosen, all modes adhere to the rules of the [Auto](ChunkSize::Auto) mode with RAM constraints.
+    ///
+    /// New modes may be added in minor releases, so a `match` on it needs a wildcard arm.
     #[derive(Debug, Clone, Copy)]
+    #[non_exhaustive]
     pub enum ChunkSize {
         /// Automatically determines an optimal chunk size based on previous read times and available RAM,
         /// ensuring it does not exceed 85% of the available RAM per iteration.
@@ -77,29 +279,338 @@ pub mod data_chunk {
         Percent(f64),
         /// Allows users to manually set the chunk size in bytes, subject to RAM constraints.
         Bytes(usize),
+        /// Places chunk boundaries by the content of the file (rolling Gear hash, as in FastCDC),
+        /// so inserting or removing bytes only changes the chunks around the edit.
+        ///
+        /// Chunks are between `min` and `max` bytes long and `avg` bytes on average. The RAM limit
+        /// takes precedence over `max`. Delimiters and the line mode are ignored in this mode.
+        ContentDefined {
+            /// Minimum chunk size in bytes (except the last chunk)
+            min: usize,
+            /// Desired average chunk size in bytes
+            avg: usize,
+            /// Maximum chunk size in bytes
+            max: usize,
+        },
+        /// Sizes chunks so that reading each one takes roughly the given time, using the read speed
+        /// of the previous chunk. Useful when the latency per chunk matters more than the number of bytes
+        /// (progress updates, cooperative multitasking).
+        ///
+        /// The first chunk is 0.1% of the file (64 KiB if the size is unknown), after that the size
+        /// changes by at most 2 times per iteration, subject to RAM constraints.
+        TargetDuration(Duration),
+    }
+
+    /// Determines the size of the next chunk in the [Auto](ChunkSize::Auto) mode.
+    ///
+    /// The default implementation is [`AutoStrategy`], a custom one can be set with `set_strategy`
+    /// of a file iterator or stream. The returned size is always limited to 85% of the available RAM.
+    /// The strategy is `Send + Sync`, so the file iterators and streams that own it stay `Send + Sync`.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::{iterator::FileIter, ChunkStrategy};
+    ///
+    /// /// Doubles the chunk size up to 1 MiB
+    /// struct Doubling;
+    ///
+    /// impl ChunkStrategy for Doubling {
+    ///     fn next_chunk_size(&mut self, prev: f64, _: f64, _: Option<f64>, _: f64) -> f64 {
+    ///         if prev > 0.0 { (prev * 2.0).min(1024.0 * 1024.0) } else { 4096.0 }
+    ///     }
+    /// }
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let file_iter = FileIter::new("file.bin")?.set_strategy(Doubling);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub trait ChunkStrategy: Send + Sync {
+        /// Returns the size of the next chunk in bytes.
+        ///
+        /// ### Arguments
+        /// - `prev`: The size of the previous chunk in bytes, not positive before the first chunk.
+        /// - `bytes_per_second`: Read speed of the previous chunk, not positive if it is unknown.
+        /// - `file_size`: The size of the source in bytes, `None` if it is unknown.
+        /// - `ram_available`: The available RAM in bytes.
+        fn next_chunk_size(
+            &mut self,
+            prev: f64,
+            bytes_per_second: f64,
+            file_size: Option<f64>,
+            ram_available: f64,
+        ) -> f64;
+    }
+
+    #[cfg(feature = "debug")]
+    impl std::fmt::Debug for dyn ChunkStrategy {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("ChunkStrategy")
+        }
+    }
+
+    /// The default [`ChunkStrategy`]: searches for the chunk size with the highest read speed
+    /// (see [How it works](crate#how-it-works)).
+    ///
+    /// The first chunk is 0.1% of the file (64 KiB if the size is unknown). After that the size keeps
+    /// moving in the same direction (up first) and turns back when the read speed drops twice in a row,
+    /// a single slower read is treated as noise. Each step changes the size by up to 15%: a turn halves the step
+    /// (down to 1%), a faster read increases it by 10%. If the read speed keeps growing with the chunk size (e.g. a fixed latency per read),
+    /// the chunk grows by 15% per iteration up to the limits. Otherwise the size converges to the fastest one
+    /// and follows it if the reader becomes slower or faster.
+    #[derive(Debug, Clone, Copy)]
+    pub struct AutoStrategy {
+        /// Read speed of the chunk before the previous one, not positive if it is unknown
+        last_bytes_per_second: f64,
+        /// Relative change of the chunk size in the next step
+        step: f64,
+        growing: bool,
+        /// The previous read was slower than the one before it, the next slower read turns back
+        slower: bool,
+    }
+
+    impl AutoStrategy {
+        const MAX_STEP: f64 = 0.15;
+        const MIN_STEP: f64 = 0.01;
+
+        /// Creates the strategy in its initial state, the first chunk is 0.1% of the file
+        pub fn new() -> Self {
+            Self {
+                last_bytes_per_second: 0.0,
+                step: AutoStrategy::MAX_STEP,
+                growing: true,
+                slower: false,
+            }
+        }
+    }
+
+    impl Default for AutoStrategy {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl ChunkStrategy for AutoStrategy {
+        fn next_chunk_size(
+            &mut self,
+            prev: f64,
+            bytes_per_second: f64,
+            file_size: Option<f64>,
+            ram_available: f64,
+        ) -> f64 {
+            if prev <= 0.0 {
+                *self = AutoStrategy::new();
+                return match file_size {
+                    Some(size) => ChunkSize::default_chunk_size(size, ram_available),
+                    None => ChunkSize::unknown_size_chunk(ram_available),
+                };
+            }
+            // the speed of the previous chunk was not measured, nothing to compare
+            if bytes_per_second <= 0.0 {
+                return prev;
+            }
+
+            if self.last_bytes_per_second > 0.0 {
+                if bytes_per_second >= self.last_bytes_per_second {
+                    self.slower = false;
+                    self.step = (self.step * 1.1).min(AutoStrategy::MAX_STEP);
+                } else if self.slower {
+                    self.slower = false;
+                    self.growing = !self.growing;
+                    self.step = (self.step / 2.0).max(AutoStrategy::MIN_STEP);
+                } else {
+                    self.slower = true;
+                }
+            }
+            self.last_bytes_per_second = bytes_per_second;
+
+            if self.growing {
+                prev * (1.0 + self.step)
+            } else {
+                prev / (1.0 + self.step)
+            }
+            .min(ChunkSize::max_chunk(ram_available))
+        }
     }
 
     #[cfg_attr(feature = "debug", derive(Debug))]
     pub struct ChunkInfo {
-        pub now_bytes_per_second: f64,
+        /// Read speed of the previous chunk, not positive if it is unknown
+        pub bytes_per_second: f64,
         pub mode: ChunkSize,
-        pub prev_bytes_per_second: f64,
+        /// Size of the previous chunk, not positive before the first chunk
+        pub chunk_size: f64,
+        /// Determines the chunk size in the Auto mode
+        pub strategy: Box<dyn ChunkStrategy>,
+        /// Bounds of the chunk size in the Auto and TargetDuration modes (the RAM limit takes precedence)
+        pub min_chunk: usize,
+        pub max_chunk: usize,
+    }
+
+    /// Progress of the reading, passed to the `on_progress` callback after every chunk
+    #[cfg_attr(feature = "debug", derive(Debug))]
+    #[derive(Clone, Copy)]
+    pub struct Progress {
+        /// Number of bytes read so far (from the start position)
+        pub bytes_read: usize,
+        /// The size of the source in bytes, `None` if it is unknown
+        pub total_bytes: Option<usize>,
+        /// Position in the source as a percentage of its size, `None` if the size is unknown
+        pub percent: Option<f64>,
+        /// Read speed of the last chunk in bytes per second
+        pub bytes_per_second: f64,
+        /// Bytes read divided by the time since the reading started, including the processing of the chunks
+        pub average_bytes_per_second: f64,
+        /// Estimated time remaining based on the average speed, `None` if the size or the speed is unknown
+        pub eta: Option<Duration>,
+    }
+
+    /// Accumulates the [`Progress`] and passes it to the callback
+    pub struct ProgressTracker {
+        /// Behind a `Mutex` only to be `Sync` without requiring it from the callback, it is never locked
+        callback: Mutex<Box<dyn FnMut(Progress) + Send>>,
+        /// When the first chunk started to be read
+        started: Option<Instant>,
+        bytes_read: usize,
+    }
+
+    #[cfg(feature = "debug")]
+    impl std::fmt::Debug for ProgressTracker {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ProgressTracker")
+                .field("started", &self.started)
+                .field("bytes_read", &self.bytes_read)
+                .finish()
+        }
     }
 
+    impl ProgressTracker {
+        pub fn new<F: FnMut(Progress) + Send + 'static>(callback: F) -> Self {
+            Self {
+                callback: Mutex::new(Box::new(callback)),
+                started: None,
+                bytes_read: 0,
+            }
+        }
+
+        /// Starts the clock of the average speed, called before every chunk (only the first call counts)
+        pub fn start(&mut self) {
+            self.started.get_or_insert_with(Instant::now);
+        }
+
+        /// Reports a chunk of `bytes` that has just been read at `bytes_per_second`, `position` is the position after it
+        pub fn update(
+            &mut self,
+            bytes: usize,
+            bytes_per_second: f64,
+            size: Option<f64>,
+            position: usize,
+        ) {
+            self.bytes_read += bytes;
+            let elapsed = self
+                .started
+                .map_or(0.0, |started| started.elapsed().as_secs_f64());
+            let average_bytes_per_second = if elapsed > 0.0 {
+                self.bytes_read as f64 / elapsed
+            } else {
+                bytes_per_second
+            };
+            let remaining = size.map(|size| (size - position as f64).max(0.0));
+
+            let callback = self
+                .callback
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner);
+            callback(Progress {
+                bytes_read: self.bytes_read,
+                total_bytes: size.map(|size| size as usize),
+                percent: size.map(|size| {
+                    if size > 0.0 {
+                        (position as f64 / size * 100.0).min(100.0)
+                    } else {
+                        100.0
+                    }
+                }),
+                bytes_per_second,
+                average_bytes_per_second,
+                eta: remaining.and_then(|remaining| {
+                    if remaining == 0.0 {
+                        Some(Duration::ZERO)
+                    } else if average_bytes_per_second > 0.0 {
+                        Duration::try_from_secs_f64(remaining / average_bytes_per_second).ok()
+                    } else {
+                        None
+                    }
+                }),
+            });
+        }
+    }
+
+    /// Spreads the reads over time so that the average read speed does not exceed the limit
     #[cfg_attr(feature = "debug", derive(Debug))]
+    pub struct RateLimit {
+        bytes_per_second: f64,
+        /// The earliest moment the next read may start, `None` before the first read
+        next: Option<Instant>,
+    }
 
+    impl RateLimit {
+        /// `None` if `bytes_per_second` is `0` (no limit)
+        pub fn new(bytes_per_second: usize) -> Option<Self> {
+            (bytes_per_second > 0).then_some(Self {
+                bytes_per_second: bytes_per_second as f64,
+                next: None,
+            })
+        }
+
+        /// Accounts for `bytes` that have just been read, returns the moment the next read may start.
+        ///
+        /// The time that was not used (e.g. while the chunk was being processed) is not accumulated,
+        /// so a long pause does not allow a burst of reads afterwards.
+        pub fn consume(&mut self, bytes: usize) -> Instant {
+            let now = Instant::now();
+            let next = self.next.unwrap_or(now)
+                + Duration::from_secs_f64(bytes as f64 / self.bytes_per_second);
+            *self.next.insert(next.max(now))
+        }
+
+        /// Time left until the next read may start
+        pub fn delay(&self) -> Duration {
+            self.next.map_or(Duration::ZERO, |next| {
+                next.saturating_duration_since(Instant::now())
+            })
+        }
+    }
+
+    #[cfg_attr(feature = "debug", derive(Debug))]
     pub struct FileInfo {
-        pub size: f64,
+        /// `None` if the size of the source is unknown (pipes, sockets, etc.)
+        pub size: Option<f64>,
         pub start_position: usize,
+        /// Position of the next chunk in the file
+        pub position: usize,
+        /// Number of chunks read so far
+        pub chunk_index: usize,
         pub chunk_info: ChunkInfo,
+        /// Where a chunk is allowed to end (any byte by default)
+        pub boundary: Option<Boundary>,
+        /// Maximum read speed, `None` if the reading is not limited
+        pub rate_limit: Option<RateLimit>,
+        /// Reports the progress after every chunk, `None` if there is no callback
+        pub progress: Option<ProgressTracker>,
     }
 
     impl FileInfo {
         pub fn new(size: f64, start_position: usize) -> Self {
             Self {
-                size,
+                size: Some(size),
                 start_position,
+                position: start_position,
+                chunk_index: 0,
                 chunk_info: ChunkInfo::default(),
+                boundary: None,
+                rate_limit: None,
+                progress: None,
             }
         }
     }
@@ -107,76 +618,212 @@ pub mod data_chunk {
     impl Default for FileInfo {
         fn default() -> Self {
             Self {
-                size: 0.0,
+                size: Some(0.0),
                 start_position: 0,
+                position: 0,
+                chunk_index: 0,
                 chunk_info: ChunkInfo::default(),
+                boundary: None,
+                rate_limit: None,
+                progress: None,
             }
         }
     }
 
+    /// Restricts the positions at which a chunk may end
+    #[cfg_attr(feature = "debug", derive(Debug))]
+    pub enum Boundary {
+        /// The chunk is extended up to (and including) the next occurrence of the byte sequence
+        Delimiter(Vec<u8>),
+        /// The chunk is trimmed back to the last `\n`, so it contains only complete lines
+        Lines,
+    }
+
+    /// Chunk size used instead of a share of the file when the size of the source is unknown (64 KiB)
+    const UNKNOWN_SIZE_CHUNK: f64 = 64.0 * 1024.0;
+
+    /// How many bytes are read at a time while a chunk is being extended to the next delimiter
+    pub(crate) const DELIMITER_SEARCH_STEP: usize = 8 * 1024;
+
+    /// Searches `haystack` for `delimiter`, starting at the `from` index.
+    ///
+    /// Returns the position right after the first occurrence, i.e. the length of the chunk
+    /// that ends with the delimiter.
+    pub(crate) fn find_delimiter(haystack: &[u8], delimiter: &[u8], from: usize) -> Option<usize> {
+        haystack
+            .get(from..)?
+            .windows(delimiter.len())
+            .position(|window| window == delimiter)
+            .map(|index| from + index + delimiter.len())
+    }
+
+    impl ChunkInfo {
+        /// Clamps the chunk size between the bounds, the RAM limit takes precedence
+        fn bounded(&self, chunk: f64, ram_available: f64) -> f64 {
+            chunk
+                .min(self.max_chunk as f64)
+                .max(self.min_chunk as f64)
+                .min(ChunkSize::max_chunk(ram_available))
+        }
+    }
+
     impl Default for ChunkInfo {
         fn default() -> Self {
             Self {
-                now_bytes_per_second: -1.0,
+                bytes_per_second: 0.0,
                 mode: ChunkSize::Auto,
-                prev_bytes_per_second: -1.0,
+                chunk_size: -1.0,
+                strategy: Box::new(AutoStrategy::new()),
+                min_chunk: 0,
+                max_chunk: usize::MAX,
             }
         }
     }
 
+    /// Returns the length of the longest prefix of `bytes` that does not end in the middle of a UTF-8 character.
+    ///
+    /// Invalid sequences are not checked here, they are reported during the conversion to `String`.
+    pub(crate) fn utf8_boundary(bytes: &[u8]) -> usize {
+        // a character takes at most 4 bytes, so only the last 3 bytes can belong to an incomplete one
+        for (index, byte) in bytes.iter().enumerate().rev().take(3) {
+            let width = match byte {
+                0b1000_0000..=0b1011_1111 => continue,
+                0b1100_0000..=0b1101_1111 => 2,
+                0b1110_0000..=0b1110_1111 => 3,
+                0b1111_0000..=0b1111_0111 => 4,
+                _ => 1,
+            };
+            return if index + width > bytes.len() {
+                index
+            } else {
+                bytes.len()
+            };
+        }
+        bytes.len()
+    }
+
+    /// Joins `data` with the incomplete character left over from the previous chunk and converts it to `String`.
+    ///
+    /// A new incomplete character at the end is moved to `pending`. Returns `None` if `data`
+    /// does not complete a single character.
+    pub(crate) fn complete_utf8(
+        pending: &mut Vec<u8>,
+        data: Vec<u8>,
+    ) -> Option<io::Result<String>> {
+        let mut bytes = if pending.is_empty() {
+            data
+        } else {
+            let mut bytes = std::mem::take(pending);
+            bytes.extend_from_slice(&data);
+            bytes
+        };
+        *pending = bytes.split_off(utf8_boundary(&bytes));
+        if bytes.is_empty() {
+            None
+        } else {
+            Some(
+                String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            )
+        }
+    }
+
+    /// The error returned when the data ends in the middle of a UTF-8 character
+    pub(crate) fn incomplete_utf8() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "incomplete UTF-8 character at the end of the data",
+        )
+    }
+
+    /// Random values for the Gear rolling hash (generated with SplitMix64 from a fixed seed,
+    /// so the chunk boundaries are the same between runs and versions)
+    const GEAR: [u64; 256] = {
+        let mut table = [0u64; 256];
+        let mut state = 0x6765_745f_6368_756e_u64;
+        let mut index = 0;
+        while index < table.len() {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut value = state;
+            value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            table[index] = value ^ (value >> 31);
+            index += 1;
+        }
+        table
+    };
+
     impl ChunkSize {
         pub(crate) fn calculate_chunk(
             prev: f64,
             now: f64,
-            size: f64,
+            size: Option<f64>,
             ram: f64,
-            mode: ChunkSize,
+            info: &mut ChunkInfo,
         ) -> f64 {
-            match mode {
+            match info.mode {
                 ChunkSize::Auto => {
-                    if prev > 0.0 {
-                        if now > 0.0 {
-                            if now < prev {
-                                ChunkSize::decrease_chunk(ram, prev, now)
-                            } else {
-                                ChunkSize::increase_chunk(ram, prev, now)
-                            }
-                        } else {
-                            prev
-                        }
-                    } else {
-                        ChunkSize::default_chunk_size(size, ram)
-                    }
+                    let chunk = info.strategy.next_chunk_size(prev, now, size, ram);
+                    info.bounded(chunk, ram)
+                }
+                ChunkSize::Percent(percent) => match size {
+                    Some(size) => ChunkSize::percentage_chunk(size, ram, percent),
+                    None => ChunkSize::unknown_size_chunk(ram),
+                },
+                ChunkSize::Bytes(bytes) => {
+                    ChunkSize::bytes_chunk(size.unwrap_or(f64::MAX), ram, bytes)
+                }
+                ChunkSize::ContentDefined { max, .. } => {
+                    ChunkSize::bytes_chunk(size.unwrap_or(f64::MAX), ram, max)
                 }
-                ChunkSize::Percent(percent) => ChunkSize::percentage_chunk(size, ram, percent),
-                ChunkSize::Bytes(bytes) => ChunkSize::bytes_chunk(size, ram, bytes),
+                ChunkSize::TargetDuration(duration) => info.bounded(
+                    ChunkSize::target_duration_chunk(prev, now, size, duration),
+                    ram,
+                ),
             }
         }
 
-        fn increase_chunk(
-            ram_available: f64,
-            prev_bytes_per_second: f64,
-            now_bytes_per_second: f64,
-        ) -> f64 {
-            (prev_bytes_per_second
-                * (1.0
-                    + ((now_bytes_per_second - prev_bytes_per_second) / prev_bytes_per_second)
-                        .min(0.15)))
-            .min(ram_available * 0.85)
-            .min(f64::MAX)
+        /// Finds the content-defined end of the chunk at the start of `data` (`data.len()` is the hard maximum).
+        ///
+        /// Uses normalized chunking: until `avg` bytes a stricter mask is applied, after that a looser one,
+        /// which keeps the chunk sizes close to `avg`.
+        pub(crate) fn content_defined_cut(data: &[u8], min: usize, avg: usize) -> usize {
+            let min = min.min(data.len());
+            let avg = avg.clamp(min.max(2), data.len().max(2));
+            let bits = avg.ilog2();
+            // the highest bits of the Gear hash depend on the last 64 bytes
+            let strict_mask = !0u64 << (64 - (bits + 1).min(63));
+            let loose_mask = !0u64 << (64 - (bits - 1).max(1));
+
+            let mut hash = 0u64;
+            for (index, byte) in data.iter().enumerate().skip(min) {
+                hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+                let mask = if index < avg { strict_mask } else { loose_mask };
+                if hash & mask == 0 {
+                    return index + 1;
+                }
+            }
+            data.len()
         }
 
-        fn decrease_chunk(
-            ram_available: f64,
-            prev_bytes_per_second: f64,
-            now_bytes_per_second: f64,
+        /// The largest chunk that may be kept in memory (85% of the available RAM)
+        pub(crate) fn max_chunk(ram_available: f64) -> f64 {
+            ram_available * 0.85
+        }
+
+        fn target_duration_chunk(
+            prev: f64,
+            bytes_per_second: f64,
+            file_size: Option<f64>,
+            duration: Duration,
         ) -> f64 {
-            (prev_bytes_per_second
-                * (1.0
-                    - ((prev_bytes_per_second - now_bytes_per_second) / prev_bytes_per_second)
-                        .min(0.45)))
-            .min(ram_available * 0.85)
-            .min(f64::MAX)
+            if prev <= 0.0 {
+                return file_size.map_or(UNKNOWN_SIZE_CHUNK, |size| size * (0.1 / 100.0));
+            }
+            if bytes_per_second <= 0.0 {
+                // the read was too fast to be measured
+                return prev * 2.0;
+            }
+            (bytes_per_second * duration.as_secs_f64()).clamp(prev / 2.0, prev * 2.0)
         }
 
         fn default_chunk_size(file_size: f64, ram_available: f64) -> f64 {
@@ -185,6 +832,10 @@ pub mod data_chunk {
                 .min(f64::MAX)
         }
 
+        fn unknown_size_chunk(ram_available: f64) -> f64 {
+            UNKNOWN_SIZE_CHUNK.min(ram_available * 0.85)
+        }
+
         fn percentage_chunk(file_size: f64, ram_available: f64, percentage: f64) -> f64 {
             (file_size * (percentage.clamp(0.1, 100.0) / 100.0)).min(ram_available * 0.85)
         }
@@ -0,0 +1,165 @@
+use super::data_chunk::{
+    find_delimiter, Boundary, Chunk, ChunkSize, FileInfo, DELIMITER_SEARCH_STEP,
+};
+use std::io;
+use std::ops::Deref;
+use std::task::{ready, Context, Poll};
+use std::time::Instant;
+
+/// A buffer the chunks are read into
+pub(crate) trait ChunkBuffer: Deref<Target = [u8]> {
+    /// Splits off the first `end` bytes as the chunk, the rest stays in the buffer
+    fn split_chunk(&mut self, end: usize) -> Self;
+}
+
+impl ChunkBuffer for Vec<u8> {
+    fn split_chunk(&mut self, end: usize) -> Self {
+        let tail = self.split_off(end);
+        std::mem::replace(self, tail)
+    }
+}
+
+/// The chunks share the allocation of the buffer, `reserve` reuses it once they are dropped
+#[cfg(feature = "bytes")]
+impl ChunkBuffer for bytes::BytesMut {
+    fn split_chunk(&mut self, end: usize) -> Self {
+        self.split_to(end)
+    }
+}
+
+/// State of a chunk whose reading has not been completed yet, kept by the streams between polls
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub(crate) struct PendingChunk<B = Vec<u8>> {
+    buffer: B,
+    /// Number of bytes carried over from the previous chunk
+    carried: usize,
+    chunk_size: usize,
+    /// The chunk may be moved to a boundary, but never beyond this limit
+    limit: usize,
+    timer: Instant,
+    /// The source has no more data
+    eof: bool,
+    /// Position from which the delimiter is searched, `None` until the search starts
+    search_from: Option<usize>,
+}
+
+impl<B: ChunkBuffer> PendingChunk<B> {
+    /// Starts a new chunk with the data carried over from the previous one
+    pub(crate) fn new(tail: B, chunk_size: usize, ram_available: f64) -> Self {
+        PendingChunk {
+            carried: tail.len(),
+            buffer: tail,
+            chunk_size,
+            limit: (ChunkSize::max_chunk(ram_available) as usize).max(chunk_size),
+            timer: Instant::now(),
+            eof: false,
+            search_from: None,
+        }
+    }
+
+    /// Reads the chunk up to its size and then, if needed, until the boundary, the limit is reached or the data runs out.
+    ///
+    /// `read_more` polls a single read of at most the given number of bytes, appending them to the buffer
+    /// (`0` means there is no more data). Returns the length of the chunk, anything past it belongs to the next chunk.
+    /// After `Poll::Pending` the method continues from where it stopped.
+    pub(crate) fn poll_end<F>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mode: ChunkSize,
+        boundary: Option<&Boundary>,
+        mut read_more: F,
+    ) -> Poll<io::Result<usize>>
+    where
+        F: FnMut(&mut Context<'_>, &mut B, usize) -> Poll<io::Result<usize>>,
+    {
+        let (buffer, chunk_size, limit) = (&mut self.buffer, self.chunk_size, self.limit);
+
+        while !self.eof && buffer.len() < chunk_size {
+            self.eof = ready!(read_more(cx, buffer, chunk_size - buffer.len()))? == 0;
+        }
+
+        let (delimiter, from) = match (mode, boundary) {
+            // the end of the chunk depends only on its content
+            (ChunkSize::ContentDefined { min, avg, .. }, _) => {
+                return Poll::Ready(Ok(ChunkSize::content_defined_cut(buffer, min, avg)))
+            }
+            (_, Some(Boundary::Delimiter(delimiter))) => (
+                delimiter.as_slice(),
+                chunk_size.saturating_sub(delimiter.len()),
+            ),
+            // a shorter read means the end of the file, the last line is returned as is
+            (_, Some(Boundary::Lines)) if buffer.len() >= chunk_size => {
+                match buffer[..chunk_size].iter().rposition(|byte| *byte == b'\n') {
+                    Some(index) => return Poll::Ready(Ok(index + 1)),
+                    None => (b"\n".as_slice(), chunk_size),
+                }
+            }
+            _ => return Poll::Ready(Ok(buffer.len())),
+        };
+
+        let from = self.search_from.get_or_insert(from);
+        loop {
+            if let Some(end) = find_delimiter(buffer, delimiter, *from) {
+                return Poll::Ready(Ok(end));
+            }
+            if buffer.len() >= limit {
+                return Poll::Ready(Ok(limit));
+            }
+            *from = (*from).max(buffer.len().saturating_sub(delimiter.len() - 1));
+            if self.eof {
+                return Poll::Ready(Ok(buffer.len()));
+            }
+            self.eof = ready!(read_more(
+                cx,
+                buffer,
+                (limit - buffer.len()).min(DELIMITER_SEARCH_STEP)
+            ))? == 0;
+        }
+    }
+
+    /// Moves the data read so far into another type of buffer
+    #[cfg(feature = "bytes")]
+    pub(crate) fn map_buffer<C>(self, map: impl FnOnce(B) -> C) -> PendingChunk<C> {
+        PendingChunk {
+            buffer: map(self.buffer),
+            carried: self.carried,
+            chunk_size: self.chunk_size,
+            limit: self.limit,
+            timer: self.timer,
+            eof: self.eof,
+            search_from: self.search_from,
+        }
+    }
+
+    /// Cuts the read data at `end` and advances the position in `metadata`.
+    ///
+    /// Returns the chunk and the data that is carried over to the next chunk.
+    pub(crate) fn finish(self, end: usize, metadata: &mut FileInfo) -> (Chunk<B>, B) {
+        let PendingChunk {
+            buffer: mut tail,
+            carried,
+            timer,
+            ..
+        } = self;
+        let buffer = tail.split_chunk(end);
+
+        let timer = timer.elapsed();
+        let offset = metadata.position;
+        let index = metadata.chunk_index;
+        if !buffer.is_empty() {
+            metadata.position += buffer.len();
+            metadata.chunk_index += 1;
+        }
+        let chunk = Chunk {
+            bytes_per_second: if !timer.is_zero() {
+                (buffer.len() + tail.len()).saturating_sub(carried) as f64 / timer.as_secs_f64()
+            } else {
+                0.0
+            },
+            value: buffer,
+            offset,
+            index,
+        };
+        (chunk, tail)
+    }
+}
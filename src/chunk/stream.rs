@@ -1,11 +1,21 @@
-use super::data_chunk::{Chunk, ChunkSize, FileInfo};
-use super::Memory;
+use super::data_chunk::{
+    complete_utf8, incomplete_utf8, Boundary, Chunk, ChunkMetadata, ChunkSize, ChunkStrategy,
+    FileInfo, Progress, ProgressTracker, RateLimit,
+};
+use super::poll::{ChunkBuffer, PendingChunk};
+use super::{Memory, MemoryProvider};
 use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
 
+use std::collections::VecDeque;
 use std::io::Cursor;
-use tokio::time::Instant;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
+use tokio::sync::{mpsc, Notify};
 use tokio::task::{self, JoinHandle};
+use tokio::time::{self, Sleep};
 use tokio::{
     fs::File,
     io::{self, AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, BufReader},
@@ -16,26 +26,19 @@ use tokio_stream::Stream;
 pub use tokio_stream::StreamExt;
 
 #[cfg_attr(feature = "debug", derive(Debug))]
-struct FilePack<R>
+struct FilePack<R, B = Vec<u8>>
 where
     R: AsyncRead + Unpin + Send,
 {
     metadata: FileInfo,
-    buffer: Option<BufReader<R>>,
+    buffer: BufReader<R>,
     read_complete: bool,
-}
-
-impl<R> Default for FilePack<R>
-where
-    R: AsyncRead + Unpin + Send,
-{
-    fn default() -> Self {
-        FilePack {
-            metadata: FileInfo::default(),
-            buffer: None,
-            read_complete: false,
-        }
-    }
+    /// Data that has already been read but belongs to the next chunk
+    tail: B,
+    /// The chunk that is being read, kept between polls
+    pending: Option<PendingChunk<B>>,
+    /// Pause before the next chunk imposed by the rate limit
+    delay: Option<Pin<Box<Sleep>>>,
 }
 
 impl FilePack<File> {
@@ -45,8 +48,11 @@ impl FilePack<File> {
                 buffer.get_ref().metadata().await?.len() as f64,
                 start_position,
             ),
-            buffer: Some(buffer),
+            buffer,
             read_complete: false,
+            tail: Vec::new(),
+            pending: None,
+            delay: None,
         })
     }
 
@@ -62,8 +68,11 @@ impl FilePack<Cursor<Vec<u8>>> {
     ) -> io::Result<FilePack<Cursor<Vec<u8>>>> {
         Ok(FilePack {
             metadata: FileInfo::new(buffer.get_ref().get_ref().len() as f64, start_position),
-            buffer: Some(buffer),
+            buffer,
             read_complete: false,
+            tail: Vec::new(),
+            pending: None,
+            delay: None,
         })
     }
 
@@ -73,46 +82,178 @@ impl FilePack<Cursor<Vec<u8>>> {
 }
 
 impl<R: AsyncRead + Unpin + Send> FilePack<R> {
-    async fn read_chunk(mut self) -> io::Result<(Chunk, Self)> {
-        let mut buffer = Vec::new();
-        match self.buffer.as_mut() {
-            Some(buff) => {
-                let timer = Instant::now();
-                match buff
-                    .take(self.metadata.chunk_info.prev_bytes_per_second.max(1.0) as u64)
-                    .read_to_end(&mut buffer)
-                    .await
+    fn with_size(buffer: BufReader<R>, size: Option<f64>, start_position: usize) -> FilePack<R> {
+        FilePack {
+            metadata: FileInfo {
+                size,
+                ..FileInfo::new(0.0, start_position)
+            },
+            buffer,
+            read_complete: false,
+            tail: Vec::new(),
+            pending: None,
+            delay: None,
+        }
+    }
+
+    /// Discards the data that was read for the previous position, called when the position changes
+    fn discard_read_ahead(&mut self) {
+        self.tail.clear();
+        self.pending = None;
+    }
+
+    /// Moves the data read so far into a `BytesMut` the next chunks are split off
+    #[cfg(feature = "bytes")]
+    fn into_bytes(self) -> FilePack<R, bytes::BytesMut> {
+        FilePack {
+            metadata: self.metadata,
+            buffer: self.buffer,
+            read_complete: self.read_complete,
+            tail: bytes::BytesMut::from(&self.tail[..]),
+            pending: self
+                .pending
+                .map(|pending| pending.map_buffer(|buffer| bytes::BytesMut::from(&buffer[..]))),
+            delay: self.delay,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send, B: StreamBuffer> FilePack<R, B> {
+    /// Determines the size of the next chunk and polls its reading, `None` if there is no more data
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+        memory: &mut Memory,
+    ) -> Poll<Option<io::Result<Chunk<B>>>> {
+        // the size is determined once per chunk, before its reading starts
+        if self.pending.is_none() {
+            if let Some(progress) = self.metadata.progress.as_mut() {
+                progress.start();
+            }
+            if let Some(delay) = self.delay.as_mut() {
+                ready!(delay.as_mut().poll(cx));
+                self.delay = None;
+            }
+            self.metadata.chunk_info.chunk_size = ChunkSize::calculate_chunk(
+                self.metadata.chunk_info.chunk_size,
+                self.metadata.chunk_info.bytes_per_second,
+                self.metadata.size,
                 {
-                    Ok(_) => {
-                        let timer = timer.elapsed();
-                        if buffer.is_empty() {
-                            self.read_complete = true;
-                        }
-                        Ok((
-                            Chunk {
-                                bytes_per_second: if !timer.is_zero() {
-                                    buffer.len() as f64 / timer.as_secs_f64()
-                                } else {
-                                    self.metadata.chunk_info.prev_bytes_per_second
-                                },
-                                value: buffer,
-                            },
-                            self,
-                        ))
+                    memory.update_ram();
+                    memory.ram_available
+                },
+                &mut self.metadata.chunk_info,
+            );
+        }
+        match ready!(self.poll_read_chunk(cx, memory.ram_available)) {
+            Ok(chunk) => {
+                self.metadata.chunk_info.bytes_per_second = chunk.bytes_per_second;
+                if let Some(rate_limit) = self.metadata.rate_limit.as_mut() {
+                    let next = rate_limit.consume(chunk.value.len());
+                    self.delay = Some(Box::pin(time::sleep_until(next.into())));
+                }
+                if let Some(progress) = self.metadata.progress.as_mut() {
+                    if !chunk.value.is_empty() {
+                        progress.update(
+                            chunk.value.len(),
+                            chunk.bytes_per_second,
+                            self.metadata.size,
+                            self.metadata.position,
+                        );
                     }
-                    Err(e) => Err(e),
                 }
+                if !chunk.value.is_empty() {
+                    Poll::Ready(Some(Ok(chunk)))
+                } else {
+                    Poll::Ready(None)
+                }
+            }
+            Err(e) => Poll::Ready(Some(Err(e))),
+        }
+    }
+
+    /// Polls the reading of the next chunk in place, the progress is kept in `pending` between polls
+    fn poll_read_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+        ram_available: f64,
+    ) -> Poll<io::Result<Chunk<B>>> {
+        let mut pending = self.pending.take().unwrap_or_else(|| {
+            PendingChunk::new(
+                std::mem::take(&mut self.tail),
+                self.metadata.chunk_info.chunk_size.max(1.0) as usize,
+                ram_available,
+            )
+        });
+        let reader = &mut self.buffer;
+        match pending.poll_end(
+            cx,
+            self.metadata.chunk_info.mode,
+            self.metadata.boundary.as_ref(),
+            |cx, buffer, max| buffer.poll_read_more(reader, cx, max),
+        ) {
+            Poll::Ready(Ok(end)) => {
+                let (chunk, tail) = pending.finish(end, &mut self.metadata);
+                self.tail = tail;
+                if chunk.value.is_empty() {
+                    self.read_complete = true;
+                }
+                Poll::Ready(Ok(chunk))
+            }
+            // nothing is lost, the next poll continues reading the same chunk
+            Poll::Ready(Err(e)) => {
+                self.pending = Some(pending);
+                Poll::Ready(Err(e))
+            }
+            Poll::Pending => {
+                self.pending = Some(pending);
+                Poll::Pending
             }
-            None => Err(io::Error::new(
-                io::ErrorKind::OutOfMemory,
-                "buffer is empty",
-            )),
         }
     }
 }
 
-type ChunkResult<R> = io::Result<(Chunk, FilePack<R>)>;
-type Task<R> = JoinHandle<ChunkResult<R>>;
+/// A buffer `FilePack` reads the chunks into
+trait StreamBuffer: ChunkBuffer + Default {
+    /// Polls a single read of at most `max` bytes, appending them to the buffer. `0` means there is no more data.
+    fn poll_read_more<R: AsyncRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        cx: &mut Context<'_>,
+        max: usize,
+    ) -> Poll<io::Result<usize>>;
+}
+
+impl StreamBuffer for Vec<u8> {
+    fn poll_read_more<R: AsyncRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        cx: &mut Context<'_>,
+        max: usize,
+    ) -> Poll<io::Result<usize>> {
+        self.reserve(max);
+        let mut reader = reader.take(max as u64);
+        let read = reader.read_buf(self);
+        tokio::pin!(read);
+        read.poll(cx)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl StreamBuffer for bytes::BytesMut {
+    fn poll_read_more<R: AsyncRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        cx: &mut Context<'_>,
+        max: usize,
+    ) -> Poll<io::Result<usize>> {
+        self.reserve(max);
+        let mut reader = reader.take(max as u64);
+        let read = reader.read_buf(self);
+        tokio::pin!(read);
+        read.poll(cx)
+    }
+}
 
 /// The `FileStream` provides an asynchronous file stream designed to read data chunks from a file.
 ///
@@ -129,7 +270,6 @@ where
 {
     memory: Memory,
     file: FilePack<R>,
-    current_task: Option<Task<R>>,
 }
 
 impl FileStream<File> {
@@ -195,12 +335,77 @@ impl FileStream<File> {
             memory: Memory::new(),
             file: FilePack::<File>::new(FilePack::<File>::create_buffer(&path.into()).await?, 0)
                 .await?,
-            current_task: None,
         })
     }
 }
 
-impl<R: AsyncRead + AsyncSeek + Unpin + Send> FileStream<R> {
+#[cfg(any(unix, windows))]
+impl FileStream<File> {
+    /// Converts the stream into [`ConcurrentFileStream`], which reads up to `concurrency` consecutive ranges of
+    /// the file at once with positional reads and yields them in file order.
+    ///
+    /// The size of the ranges is determined once by the selected [`mode`](crate::ChunkSize), as for the first chunk,
+    /// and is limited so that the ranges read at once fit into 85% of the available RAM.
+    /// Delimiters, the line mode and content-defined cuts are not applied to the ranges.
+    /// A `concurrency` of `0` is treated as `1`. Requires a running tokio runtime.
+    ///
+    /// ### Arguments
+    /// - `concurrency`: The maximum number of ranges read at once.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::stream::{FileStream, StreamExt};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let mut file_stream = FileStream::new("file.bin").await?.concurrent(8).await;
+    ///     while let Some(chunk) = file_stream.next().await {
+    ///         // ...
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn concurrent(mut self, concurrency: usize) -> ConcurrentFileStream {
+        let concurrency = concurrency.max(1);
+        self.memory.update_ram();
+        let size = self.file.metadata.size.unwrap_or_default() as usize;
+        let chunk_size = ChunkSize::calculate_chunk(
+            0.0,
+            0.0,
+            self.file.metadata.size,
+            self.memory.ram_available,
+            &mut self.file.metadata.chunk_info,
+        )
+        .min(ChunkSize::max_chunk(self.memory.ram_available) / concurrency as f64)
+        .max(1.0) as usize;
+        ConcurrentFileStream {
+            file: Arc::new(self.file.buffer.into_inner().into_std().await),
+            position: self.file.metadata.position.min(size),
+            size,
+            chunk_size,
+            concurrency,
+            reads: VecDeque::with_capacity(concurrency),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send> FileStream<R> {
+    /// Creates a new `FileStream` from a source of unknown size that cannot seek, such as
+    /// `stdin`, a pipe of a child process or a socket.
+    ///
+    /// Since the size is unknown, the [`Auto`](crate::ChunkSize::Auto) mode starts from 64 KiB chunks
+    /// and the [`Percent`](crate::ChunkSize::Percent) mode always uses 64 KiB chunks (both still limited by RAM).
+    /// [`get_file_size`](FileStream::get_file_size) returns `0.0`.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    pub fn from_reader_without_size(reader: R) -> FileStream<R> {
+        FileStream {
+            memory: Memory::new(),
+            file: FilePack::with_size(BufReader::new(reader), None, 0),
+        }
+    }
+
     /// Checks if the read operation is complete, returning `true` if the data buffer is empty.
     ///
     /// ---
@@ -211,12 +416,12 @@ impl<R: AsyncRead + AsyncSeek + Unpin + Send> FileStream<R> {
         self.file.read_complete
     }
 
-    /// Returns the size of the file in bytes.
+    /// Returns the size of the file in bytes, `0.0` if the size of the source is unknown.
     ///
     /// ---
     /// Use [`data_size_format`](crate::data_size_format) for comfortable reading and for calculating size
     pub fn get_file_size(&self) -> f64 {
-        self.file.metadata.size
+        self.file.metadata.size.unwrap_or_default()
     }
 
     /// Defines the mode of dividing the file into chunks, automatic mode or fixed size
@@ -228,6 +433,185 @@ impl<R: AsyncRead + AsyncSeek + Unpin + Send> FileStream<R> {
         self
     }
 
+    /// Replaces the [strategy](crate::ChunkStrategy) that determines the chunk size in the [`Auto`](crate::ChunkSize::Auto) mode,
+    /// [`AutoStrategy`](crate::AutoStrategy) by default.
+    ///
+    /// ### Arguments
+    /// - `strategy`: The strategy to be set.
+    pub fn set_strategy<S: ChunkStrategy + 'static>(mut self, strategy: S) -> Self {
+        self.file.metadata.chunk_info.strategy = Box::new(strategy);
+        self
+    }
+
+    /// Sets the lower bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// The RAM limit (85% of the available memory) still takes precedence.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The minimum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_min_chunk(mut self, bytes: usize) -> Self {
+        self.file.metadata.chunk_info.min_chunk = bytes;
+        self
+    }
+
+    /// Sets the upper bound of the chunk size in the [`Auto`](crate::ChunkSize::Auto) and [`TargetDuration`](crate::ChunkSize::TargetDuration) modes.
+    /// If it is lower than the [minimum](Self::set_min_chunk), the minimum wins.
+    ///
+    /// ### Arguments
+    /// - `bytes`: The maximum chunk size in bytes (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_max_chunk(mut self, bytes: usize) -> Self {
+        self.file.metadata.chunk_info.max_chunk = bytes;
+        self
+    }
+
+    /// Limits the average read speed: the stream sleeps asynchronously before reading the next chunk
+    /// until it is allowed (does not apply to [`concurrent`](FileStream::concurrent)). Requires the tokio time driver.
+    /// The limit is applied between chunks, so a single chunk is read at full speed, and a pause in
+    /// the processing of the chunks does not allow faster reads afterwards. `0` removes the limit.
+    ///
+    /// ### Arguments
+    /// - `bytes_per_second`: The maximum read speed in bytes per second (`IECUnit`/`SIUnit` of the `size_format` feature convert into it with `into()`).
+    pub fn set_rate_limit(mut self, bytes_per_second: usize) -> Self {
+        self.file.metadata.rate_limit = RateLimit::new(bytes_per_second);
+        self
+    }
+
+    /// Calls `callback` with the [progress](crate::Progress) of the reading after every chunk:
+    /// bytes read, total size, percent, current and average read speed and the estimated time remaining.
+    ///
+    /// ### Arguments
+    /// - `callback`: The function that receives the progress.
+    pub fn on_progress<F: FnMut(Progress) + Send + 'static>(mut self, callback: F) -> Self {
+        self.file.metadata.progress = Some(ProgressTracker::new(callback));
+        self
+    }
+
+    /// Include the available SWAP (available `RAM` + available `SWAP`)
+    pub fn include_available_swap(mut self) -> Self {
+        self.memory.swap_check = true;
+        self
+    }
+
+    /// Replaces the [source](crate::MemoryProvider) of the available memory that limits the chunk size,
+    /// [`SystemMemory`](crate::SystemMemory) by default.
+    ///
+    /// ### Arguments
+    /// - `provider`: The memory provider to be set (e.g. [`FixedMemory`](crate::FixedMemory)).
+    pub fn set_memory_provider<P: MemoryProvider + 'static>(mut self, provider: P) -> Self {
+        self.memory.provider = Box::new(provider);
+        self
+    }
+
+    /// Never splits a record: each chunk is extended up to (and including) the next occurrence of `delimiter`.
+    ///
+    /// The chunk size is still determined by the selected [`mode`](crate::ChunkSize), the delimiter only
+    /// moves the end of the chunk forward. Data read past the delimiter is carried over to the next iteration.
+    /// If the delimiter is not found within 85% of the available RAM, the chunk is cut at this limit.
+    /// An empty `delimiter` disables the behavior.
+    ///
+    /// ### Arguments
+    /// - `delimiter`: The byte sequence that terminates a record (e.g. `b"\n"`).
+    pub fn set_delimiter<D: Into<Vec<u8>>>(mut self, delimiter: D) -> Self {
+        self.file.metadata.boundary = Some(delimiter.into())
+            .filter(|d| !d.is_empty())
+            .map(Boundary::Delimiter);
+        self
+    }
+
+    /// Yields only complete lines: each chunk is trimmed back to its last `\n` (so `\r\n` endings stay intact),
+    /// and the rest is carried over to the next iteration.
+    ///
+    /// The chunk size is still determined by the selected [`mode`](crate::ChunkSize). If a chunk does not contain
+    /// a single `\n`, it is extended up to the end of the line, limited to 85% of the available RAM.
+    /// The last line of the file is returned even if it has no line ending.
+    pub fn set_line_mode(mut self) -> Self {
+        self.file.metadata.boundary = Some(Boundary::Lines);
+        self
+    }
+
+    /// Converts the stream into [`Utf8FileStream`], which yields `String` chunks.
+    ///
+    /// Every chunk ends on a UTF-8 character boundary: an incomplete multibyte character at the end of
+    /// a chunk is carried over to the next one. Invalid UTF-8 data results in an
+    /// [`InvalidData`](io::ErrorKind::InvalidData) error.
+    pub fn utf8(self) -> Utf8FileStream<R> {
+        Utf8FileStream {
+            stream: self,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send> FileStream<R> {
+    /// Converts the stream into [`BytesFileStream`], which yields [`Bytes`](bytes::Bytes) chunks.
+    ///
+    /// See [`BytesFileStream`] for how the chunk buffers are reused.
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+    pub fn bytes(self) -> BytesFileStream<R> {
+        BytesFileStream {
+            memory: self.memory,
+            file: self.file.into_bytes(),
+        }
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin + Send> FileStream<R> {
+    /// Creates a new `FileStream` from any source that implements [`AsyncRead`] and [`AsyncSeek`]
+    /// (a `DuplexStream` wrapper, a decompressor, an async blob reader, etc.).
+    ///
+    /// The size of the source is probed by seeking to its end, after which the reader is returned
+    /// to its current position, so reading starts from there.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    ///
+    /// ### Errors
+    /// Returns an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if one of the seek operations fails.
+    pub async fn from_reader(mut reader: R) -> io::Result<FileStream<R>> {
+        let start_position = reader.stream_position().await?;
+        let size = reader.seek(io::SeekFrom::End(0)).await?;
+        reader.seek(io::SeekFrom::Start(start_position)).await?;
+        Ok(FileStream {
+            memory: Memory::new(),
+            file: FilePack::with_size(
+                BufReader::new(reader),
+                Some(size as f64),
+                start_position as usize,
+            ),
+        })
+    }
+
+    /// Creates a new `FileStream` from any source that implements [`AsyncRead`] and [`AsyncSeek`], with an explicitly specified size.
+    ///
+    /// Useful when the size is already known or seeking to the end of the source is expensive.
+    /// The reader is expected to be at its start.
+    ///
+    /// ### Arguments
+    /// - `reader`: The source of the data.
+    /// - `size`: The size of the source in bytes.
+    ///
+    /// ## Example
+    /// ```
+    /// use get_chunk::stream::{FileStream, StreamExt};
+    /// use std::io::Cursor;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let data = b"some data without cloning";
+    ///     let mut file_stream = FileStream::from_reader_with_size(Cursor::new(data.as_slice()), data.len());
+    ///     while let Some(chunk) = file_stream.next().await {
+    ///         // ...
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_reader_with_size(reader: R, size: usize) -> FileStream<R> {
+        FileStream {
+            memory: Memory::new(),
+            file: FilePack::with_size(BufReader::new(reader), Some(size as f64), 0),
+        }
+    }
+
     /// Sets the start position for reading the file in bytes.
     ///
     /// ### Arguments
@@ -236,21 +620,21 @@ impl<R: AsyncRead + AsyncSeek + Unpin + Send> FileStream<R> {
     /// ### Errors
     /// Returns an [`io::Result`](https://doc.rust-lang.org/std/io/type.Result.html) indicating success or an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if the seek operation fails.
     pub async fn set_start_position_bytes(mut self, position: usize) -> io::Result<Self> {
-        self.file.metadata.start_position = position.min(self.file.metadata.size as usize);
-
-        match self.file.buffer.as_mut() {
-            Some(buff) => {
-                buff.seek(io::SeekFrom::Start(
-                    self.file.metadata.start_position as u64,
-                ))
-                .await?;
-                Ok(self)
-            }
-            None => Err(io::Error::new(
-                io::ErrorKind::OutOfMemory,
-                "buffer is empty",
-            )),
-        }
+        self.file.metadata.start_position = self
+            .file
+            .metadata
+            .size
+            .map_or(position, |size| position.min(size as usize));
+        self.file.metadata.position = self.file.metadata.start_position;
+        self.file.discard_read_ahead();
+
+        self.file
+            .buffer
+            .seek(io::SeekFrom::Start(
+                self.file.metadata.start_position as u64,
+            ))
+            .await?;
+        Ok(self)
     }
 
     /// Sets the start position for reading the file as a percentage of the total file size.
@@ -261,91 +645,268 @@ impl<R: AsyncRead + AsyncSeek + Unpin + Send> FileStream<R> {
     /// ### Errors
     /// Returns an [`io::Result`](https://doc.rust-lang.org/std/io/type.Result.html) indicating success or an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) if the seek operation fails.
     pub async fn set_start_position_percent(mut self, position_percent: f64) -> io::Result<Self> {
-        self.file.metadata.start_position =
-            (self.file.metadata.size * (position_percent / 100.0)).min(100.0) as usize;
-        match self.file.buffer.as_mut() {
-            Some(buff) => {
-                buff.seek(io::SeekFrom::Start(
-                    self.file.metadata.start_position as u64,
-                ))
-                .await?;
-                Ok(self)
+        self.file.metadata.start_position = (self.file.metadata.size.unwrap_or_default()
+            * (position_percent.clamp(0.0, 100.0) / 100.0))
+            as usize;
+        self.file.metadata.position = self.file.metadata.start_position;
+        self.file.discard_read_ahead();
+        self.file
+            .buffer
+            .seek(io::SeekFrom::Start(
+                self.file.metadata.start_position as u64,
+            ))
+            .await?;
+        Ok(self)
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send> FileStream<R> {
+    /// Converts the stream into [`MetadataFileStream`], which yields every chunk together with
+    /// its [metadata](crate::ChunkMetadata): offset in the file, index, mode and read speed.
+    pub fn with_metadata(self) -> MetadataFileStream<R> {
+        MetadataFileStream { stream: self }
+    }
+
+    /// Determines the size of the next chunk and polls its reading, `None` if there is no more data
+    fn poll_next_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Chunk>>> {
+        self.file.poll_next_chunk(cx, &mut self.memory)
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send + 'static> FileStream<R> {
+    /// Converts the stream into [`PrefetchFileStream`], which keeps reading up to `depth` upcoming chunks
+    /// in a background task while the current chunk is being processed.
+    ///
+    /// The prefetched chunks that have not been taken yet never occupy more than 85% of the available RAM
+    /// (the same limit that applies to a single chunk), the reading pauses until they are taken.
+    /// A `depth` of `0` is treated as `1`. Requires a running tokio runtime.
+    ///
+    /// ### Arguments
+    /// - `depth`: The maximum number of chunks read ahead.
+    pub fn prefetch(mut self, depth: usize) -> PrefetchFileStream {
+        let (sender, receiver) = mpsc::channel(depth.max(1));
+        let buffered = Arc::new(Buffered::default());
+        let task = task::spawn({
+            let buffered = Arc::clone(&buffered);
+            async move {
+                // a place in the channel is reserved before the chunk is read, so at most `depth` chunks are read ahead
+                while let Ok(permit) = sender.reserve().await {
+                    let Some(chunk) = std::future::poll_fn(|cx| self.poll_next_chunk(cx)).await
+                    else {
+                        break;
+                    };
+                    let len = chunk.as_ref().map_or(0, |chunk| chunk.value.len());
+                    buffered.bytes.fetch_add(len, Ordering::AcqRel);
+                    permit.send(chunk.map(|chunk| chunk.value));
+                    // waits until the prefetched data fits into the RAM limit
+                    loop {
+                        let taken = buffered.taken.notified();
+                        self.memory.update_ram();
+                        if buffered.bytes.load(Ordering::Acquire)
+                            <= ChunkSize::max_chunk(self.memory.ram_available) as usize
+                        {
+                            break;
+                        }
+                        taken.await;
+                    }
+                }
             }
-            None => Err(io::Error::new(
-                io::ErrorKind::OutOfMemory,
-                "buffer is empty",
-            )),
+        });
+        PrefetchFileStream {
+            receiver,
+            buffered,
+            task,
         }
     }
+}
 
-    /// Include the available SWAP (available `RAM` + available `SWAP`)
-    pub fn include_available_swap(mut self) -> Self {
-        self.memory.swap_check = true;
-        self
+impl<R: AsyncRead + Unpin + Send> Stream for FileStream<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .poll_next_chunk(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(|chunk| chunk.value)))
     }
 }
 
-impl<R: AsyncRead + AsyncSeek + Unpin + Send + 'static> Stream for FileStream<R> {
-    type Item = io::Result<Vec<u8>>;
+/// Stream adapter that yields [`ChunkMetadata`] instead of raw bytes.
+///
+/// Created by [`FileStream::with_metadata`].
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct MetadataFileStream<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    stream: FileStream<R>,
+}
+
+impl<R: AsyncRead + Unpin + Send> Stream for MetadataFileStream<R> {
+    type Item = io::Result<ChunkMetadata>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let stream = &mut self.get_mut().stream;
+        let mode = stream.file.metadata.chunk_info.mode;
+        stream.poll_next_chunk(cx).map(|chunk| {
+            chunk.map(|chunk| {
+                chunk.map(|chunk| ChunkMetadata {
+                    value: chunk.value,
+                    offset: chunk.offset,
+                    index: chunk.index,
+                    mode,
+                    bytes_per_second: chunk.bytes_per_second,
+                })
+            })
+        })
+    }
+}
+
+/// Stream adapter that yields chunks as [`Bytes`](bytes::Bytes), ready to be sent as a body of `hyper`, `tonic`, etc.
+///
+/// Created by [`FileStream::bytes`].
+///
+/// The chunks are read into a single `BytesMut` and split off it without copying,
+/// its allocation is reused for the next chunks once all the `Bytes` pointing into it are dropped.
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct BytesFileStream<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    memory: Memory,
+    file: FilePack<R, bytes::BytesMut>,
+}
 
-    fn poll_next(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Option<Self::Item>> {
-        // Оптимальный размер чанка за один вызов `poll_next`
+#[cfg(feature = "bytes")]
+impl<R: AsyncRead + Unpin + Send> Stream for BytesFileStream<R> {
+    type Item = io::Result<bytes::Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let stream = self.get_mut();
+        stream
+            .file
+            .poll_next_chunk(cx, &mut stream.memory)
+            .map(|chunk| chunk.map(|chunk| chunk.map(|chunk| chunk.value.freeze())))
+    }
+}
+
+/// Stream adapter that yields chunks as `String`, each chunk ends on a UTF-8 character boundary.
+///
+/// Created by [`FileStream::utf8`].
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Utf8FileStream<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    stream: FileStream<R>,
+    /// Bytes of the character that was split by the last chunk
+    pending: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin + Send> Stream for Utf8FileStream<R> {
+    type Item = io::Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
-        this.file.metadata.chunk_info.prev_bytes_per_second = ChunkSize::calculate_chunk(
-            this.file.metadata.chunk_info.prev_bytes_per_second,
-            this.file.metadata.chunk_info.now_bytes_per_second,
-            this.file.metadata.size,
-            {
-                this.memory.update_ram();
-                this.memory.ram_available
-            },
-            this.file.metadata.chunk_info.mode,
-        );
-        if this.current_task.is_none() {
-            // let file = Option::take(this.file);
-            this.current_task = Some(task::spawn(std::mem::take(&mut this.file).read_chunk()));
-        }
-        match this.current_task.as_mut() {
-            Some(task) => {
-                tokio::pin!(task);
-                match task.poll(cx) {
-                    std::task::Poll::Ready(task_status) => match task_status {
-                        Ok(inner) => match inner {
-                            Ok((chunk, filepack)) => {
-                                this.current_task = None;
-                                this.file = filepack;
-                                this.file.metadata.chunk_info.now_bytes_per_second =
-                                    chunk.bytes_per_second;
-                                if !chunk.value.is_empty() {
-                                    std::task::Poll::Ready(Some(Ok(chunk.value)))
-                                } else {
-                                    std::task::Poll::Ready(None)
-                                }
-                            }
-                            Err(e) => {
-                                this.current_task = None;
-                                std::task::Poll::Ready(Some(Err(e)))
-                            }
-                        },
-                        Err(e) => {
-                            this.current_task = None;
-                            std::task::Poll::Ready(Some(Err(std::io::Error::new(
-                                std::io::ErrorKind::Other,
-                                e,
-                            ))))
-                        }
-                    },
-                    std::task::Poll::Pending => std::task::Poll::Pending,
+        loop {
+            match std::pin::Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(data))) => {
+                    if let Some(text) = complete_utf8(&mut this.pending, data) {
+                        return Poll::Ready(Some(text));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) if this.pending.is_empty() => return Poll::Ready(None),
+                Poll::Ready(None) => {
+                    this.pending.clear();
+                    return Poll::Ready(Some(Err(incomplete_utf8())));
                 }
+                Poll::Pending => return Poll::Pending,
             }
-            None => std::task::Poll::Ready(None),
         }
     }
 }
 
+/// Chunks that have been prefetched but not taken yet
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Default)]
+struct Buffered {
+    /// Total size of the chunks in bytes
+    bytes: AtomicUsize,
+    /// Notified every time a chunk is taken
+    taken: Notify,
+}
+
+/// Stream adapter that reads upcoming chunks in a background task, so reading and processing overlap.
+///
+/// Created by [`FileStream::prefetch`]. Dropping the stream stops the background task.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct PrefetchFileStream {
+    receiver: mpsc::Receiver<io::Result<Vec<u8>>>,
+    buffered: Arc<Buffered>,
+    task: JoinHandle<()>,
+}
+
+impl Stream for PrefetchFileStream {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let chunk = ready!(this.receiver.poll_recv(cx));
+        if let Some(Ok(data)) = chunk.as_ref() {
+            this.buffered.bytes.fetch_sub(data.len(), Ordering::AcqRel);
+            this.buffered.taken.notify_one();
+        }
+        Poll::Ready(chunk)
+    }
+}
+
+impl Drop for PrefetchFileStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Stream adapter that reads several consecutive ranges of a file at once and yields them in file order.
+///
+/// Created by [`FileStream::concurrent`].
+#[cfg(any(unix, windows))]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ConcurrentFileStream {
+    file: Arc<std::fs::File>,
+    /// Position of the next range to be read
+    position: usize,
+    size: usize,
+    chunk_size: usize,
+    concurrency: usize,
+    /// Reads in progress, in file order
+    reads: VecDeque<JoinHandle<io::Result<Vec<u8>>>>,
+}
+
+#[cfg(any(unix, windows))]
+impl Stream for ConcurrentFileStream {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        while this.reads.len() < this.concurrency && this.position < this.size {
+            let (file, offset) = (Arc::clone(&this.file), this.position);
+            let mut buffer = vec![0; this.chunk_size.min(this.size - offset)];
+            this.position += buffer.len();
+            this.reads.push_back(task::spawn_blocking(move || {
+                super::read_exact_at(&file, &mut buffer, offset as u64).map(|_| buffer)
+            }));
+        }
+        let Some(read) = this.reads.front_mut() else {
+            return Poll::Ready(None);
+        };
+        let chunk = ready!(std::pin::Pin::new(read).poll(cx));
+        this.reads.pop_front();
+        Poll::Ready(Some(chunk.unwrap_or_else(|e| Err(io::Error::other(e)))))
+    }
+}
+
 /// Added implementations of conversions from other types
 mod impl_try_from {
     use super::*;
@@ -385,7 +946,6 @@ mod impl_try_from {
             Ok(FileStream {
                 memory: Memory::new(),
                 file: FilePack::<File>::new(BufReader::new(file), 0).await?,
-                current_task: None,
             })
         }
     }
@@ -398,7 +958,6 @@ mod impl_try_from {
             Ok(FileStream {
                 memory: Memory::new(),
                 file: FilePack::<File>::new(buffer, 0).await?,
-                current_task: None,
             })
         }
     }
@@ -415,7 +974,6 @@ mod impl_try_from {
                     0,
                 )
                 .await?,
-                current_task: None,
             })
         }
     }
@@ -428,7 +986,6 @@ mod impl_try_from {
             Ok(FileStream {
                 memory: Memory::new(),
                 file: FilePack::<Cursor<Vec<u8>>>::new(BufReader::new(buffer), 0).await?,
-                current_task: None,
             })
         }
     }
@@ -441,7 +998,6 @@ mod impl_try_from {
             Ok(FileStream {
                 memory: Memory::new(),
                 file: FilePack::<Cursor<Vec<u8>>>::new(buffer, 0).await?,
-                current_task: None,
             })
         }
     }
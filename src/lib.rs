@@ -18,9 +18,9 @@
 //!
 //! ---
 //! > It's important to note a potential scenario: Suppose a chunk is 15GB, and there's initially 16GB of free RAM.
-//! If, between the current and next "Next" calls, 2GB of RAM becomes unexpectedly occupied,
-//! the current 15GB chunk will still be processed. This situation introduces a risk,
-//! as the system might either reclaim resources (resulting in io::Error) or lead to a code crash.
+//! > If, between the current and next "Next" calls, 2GB of RAM becomes unexpectedly occupied,
+//! > the current 15GB chunk will still be processed. This situation introduces a risk,
+//! > as the system might either reclaim resources (resulting in io::Error) or lead to a code crash.
 //!
 //! ---
 //!
@@ -41,23 +41,26 @@
 //!
 //! The `calculate_chunk` function in the `ChunkSize` enum determines the optimal chunk size based on various parameters. Here's a breakdown of how the size is calculated:
 //!
-//! The variables `prev` and `now` represent the previous and current read time, respectively.
+//! The Auto mode keeps two separate quantities of the previous chunk:
 //!
 //! **prev:**
 //!
-//! *Definition:* `prev` represents the time taken to read a piece of data in the previous iteration.
+//! *Definition:* `prev` is the size of the previous chunk in bytes.
 //!
 //! **now:**
 //!
-//! *Definition:* `now` represents the current time taken to read the data fragment in the current iteration.
+//! *Definition:* `now` is the read speed of the previous chunk in bytes per second (not positive if the read was too fast to be measured).
 //!
 //! 1. **Auto Mode:**
-//!    - If the previous read time (`prev`) is greater than zero:
-//!      - If the current read time (`now`) is also greater than zero:
-//!        - If `now` is less than `prev`, decrease the chunk size using `decrease_chunk` method.
-//!        - If `now` is greater than or equal to `prev`, increase the chunk size using `increase_chunk` method.
-//!      - If `now` is zero or negative, maintain the previous chunk size (`prev`).
-//!    - If the previous read time is zero or negative, use the default chunk size based on the file size and available *RAM*.
+//!    - If there is no previous chunk (`prev` is zero or negative), use the default chunk size based on the file size and available *RAM*.
+//!    - If `now` is zero or negative, maintain the previous chunk size (`prev`).
+//!    - Otherwise `now` is compared with the read speed of the chunk before (`last`):
+//!      - If `now >= last`, the size keeps moving in the same direction (up at the start) and the step grows by 25%, up to 15%.
+//!      - If `now < last`, the direction is reversed and the step is halved, down to 1%.
+//!    - As a result the chunk grows by 15% per iteration while bigger chunks are read faster, and otherwise converges
+//!      to the size with the highest read speed, following it if the reader becomes slower or faster.
+//!    - These rules are implemented by `AutoStrategy`, a custom `ChunkStrategy` can replace them with `set_strategy`.
+//!    - The result is clamped between `set_min_chunk` and `set_max_chunk` (if set), the *RAM* limit takes precedence.
 //!
 //! 2. **Percent Mode:**
 //!    - Calculate the chunk size as a percentage of the total file size using the `percentage_chunk` method. The percentage is capped between 0.1% and 100%.
@@ -65,18 +68,29 @@
 //! 3. **Bytes Mode:**
 //!    - Calculate the chunk size based on the specified number of bytes using the `bytes_chunk` method. The size is capped by the file size and available *RAM*.
 //!
+//! 4. **ContentDefined Mode:**
+//!    - Read up to `max` bytes (capped like the **Bytes Mode**) and cut the chunk where the rolling Gear hash of the content matches a mask, using the `content_defined_cut` method.
+//!      The cut is never placed before `min` bytes, and the sizes are normalized around `avg`.
+//!
+//! 5. **TargetDuration Mode:**
+//!    - Calculate the chunk size from the read speed of the previous chunk so that reading the next one takes the given time, using the `target_duration_chunk` method.
+//!      The size changes by at most 2 times per iteration, and is clamped between `set_min_chunk` and `set_max_chunk` like in the **Auto Mode**.
+//!
+//! If the size of the source is unknown (a pipe or `stdin`, see `from_reader_without_size`), the **Auto Mode**
+//! and the **TargetDuration Mode** start from 64 KiB chunks and the **Percent Mode** always uses 64 KiB chunks, all capped by the available *RAM*.
+//!
 //! ### Key Formulas:
 //!
-//! - **Increase Chunk Size:**
+//! - **Next Chunk Size:**
 //!
 //! ```rust
-//! (prev * (1.0 + ((now - prev) / prev).min(0.15))).min(ram_available * 0.85).min(f64::MAX)
+//! if growing { prev * (1.0 + step) } else { prev / (1.0 + step) }.min(ram_available * 0.85)
 //! ```
 //!
-//! - **Decrease Chunk Size:**
+//! - **Target Duration Chunk Size:**
 //!
 //! ```rust
-//! (prev * (1.0 - ((prev - now) / prev).min(0.45))).min(ram_available * 0.85).min(f64::MAX)
+//! (now * duration.as_secs_f64()).clamp(prev / 2.0, prev * 2.0).min(ram_available * 0.85)
 //! ```
 //!
 //! - **Default Chunk Size:**
@@ -100,7 +114,8 @@
 
 mod chunk;
 
-pub use chunk::data_chunk::ChunkSize;
+pub use chunk::data_chunk::{AutoStrategy, ChunkMetadata, ChunkSize, ChunkStrategy, Progress};
+pub use chunk::{FixedMemory, MemoryProvider, SystemMemory};
 
 /// The module is responsible for the size of the data
 ///
@@ -150,7 +165,32 @@ pub use chunk::iterator;
 ///     "stream"
 /// ] }
 /// ```
-
 #[cfg(feature = "stream")]
 #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
 pub use chunk::stream;
+
+/// The module is responsible for **async** retrieval of chunks on any runtime, using the `futures::io` traits
+///
+/// ---
+/// Not activated by default `Cargo.toml` must be modified for activations
+/// ```
+/// get_chunk = { version = "x.y.z", features = [
+///     "futures_stream"
+/// ] }
+/// ```
+#[cfg(feature = "futures_stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures_stream")))]
+pub use chunk::futures_stream;
+
+/// The module is responsible for retrieval of chunks from a **memory-mapped** file
+///
+/// ---
+/// Not activated by default `Cargo.toml` must be modified for activations
+/// ```
+/// get_chunk = { version = "x.y.z", features = [
+///     "mmap"
+/// ] }
+/// ```
+#[cfg(feature = "mmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+pub use chunk::mmap;
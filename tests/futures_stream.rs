@@ -0,0 +1,219 @@
+#[cfg(all(feature = "size_format", feature = "futures_stream"))]
+mod temp_files;
+
+#[cfg(all(feature = "size_format", feature = "futures_stream"))]
+mod size_format {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::io::{AllowStdIo, Cursor};
+    use futures::{StreamExt, TryStreamExt};
+    use get_chunk::data_size_format::iec::{IECSize, IECUnit};
+    use get_chunk::futures_stream::FileStream;
+    use get_chunk::ChunkSize;
+    use std::fs::File;
+    use std::io;
+    use temp_files::{FileTest, FILE_TEST};
+
+    mod set_mode_tests {
+        use super::*;
+
+        /// Auto
+        #[test]
+        fn set_mode_t_0() -> io::Result<()> {
+            block_on(async {
+                let file_orig = FileTest::create_file_with_size(
+                    FILE_TEST,
+                    IECUnit::new(700.0, IECSize::Kibibyte).into(),
+                )?;
+
+                let mut file_from_chunks = FileTest::default();
+                let mut file_stream =
+                    FileStream::from_reader(AllowStdIo::new(File::open(file_orig.path.as_str())?))
+                        .await?;
+                while let Some(chunk) = file_stream.try_next().await? {
+                    file_from_chunks.write_bytes_to_file(&chunk)?;
+                }
+                assert!(file_stream.is_read_complete());
+                assert_eq!(file_orig, file_from_chunks);
+                Ok(())
+            })
+        }
+
+        /// Bytes
+        #[test]
+        fn set_mode_t_1() -> io::Result<()> {
+            block_on(async {
+                let data = (0..10_000).map(|index| index as u8).collect::<Vec<_>>();
+                let chunks = FileStream::from_reader_with_size(Cursor::new(&data), data.len())
+                    .set_mode(ChunkSize::Bytes(3_000))
+                    .try_collect::<Vec<_>>()
+                    .await?;
+
+                assert_eq!(
+                    chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                    [3_000, 3_000, 3_000, 1_000]
+                );
+                assert_eq!(chunks.concat(), data);
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn set_start_position_t_0() -> io::Result<()> {
+        block_on(async {
+            let file = FileTest::create_with_text(&FILE_TEST, &"Hello world :D, I'm a test file!")?;
+            let chunks = FileStream::from_reader(AllowStdIo::new(File::open(file.path.as_str())?))
+                .await?
+                .set_start_position_bytes(6)
+                .await?
+                .set_mode(ChunkSize::Bytes(5))
+                .with_metadata()
+                .map(|chunk| chunk.map(|chunk| (chunk.offset, chunk.value)))
+                .take(2)
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            assert_eq!(chunks, [(6, b"world".to_vec()), (11, b" :D, ".to_vec())]);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn set_start_position_t_1() -> io::Result<()> {
+        block_on(async {
+            let data = (0..10_000).map(|index| index as u8).collect::<Vec<_>>();
+            let chunks = FileStream::from_reader_with_size(Cursor::new(&data), data.len())
+                .set_start_position_percent(75.0)
+                .await?
+                .set_mode(ChunkSize::Bytes(1_000))
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            assert_eq!(chunks.concat(), data[7_500..]);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn line_mode_t_0() -> io::Result<()> {
+        block_on(async {
+            let text = "Привет\nмир\n你好 🌍\nworld";
+            let lines = FileStream::from_reader_without_size(text.as_bytes())
+                .set_mode(ChunkSize::Bytes(4))
+                .set_line_mode()
+                .utf8()
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            assert_eq!(lines, ["Привет\n", "мир\n", "你好 🌍\n", "world"]);
+            Ok(())
+        })
+    }
+
+    /// Delivers at most 4 bytes per read, fails once with `WouldBlock` when the data up to `fail_at` has been read
+    struct Interrupted {
+        data: &'static [u8],
+        fail_at: usize,
+    }
+
+    impl futures::io::AsyncRead for Interrupted {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+            buf: &mut [u8],
+        ) -> std::task::Poll<io::Result<usize>> {
+            if self.fail_at == 0 {
+                self.fail_at = usize::MAX;
+                return std::task::Poll::Ready(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            let len = self.data.len().min(buf.len()).min(4);
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            self.fail_at = self.fail_at.saturating_sub(len);
+            std::task::Poll::Ready(Ok(len))
+        }
+    }
+
+    #[test]
+    fn delimiter_t_0() {
+        // the data read before an error is not lost
+        let reader = Interrupted {
+            data: b"aaaa;bbbb;cccc;",
+            fail_at: 4,
+        };
+        let chunks = block_on(
+            FileStream::from_reader_without_size(reader)
+                .set_mode(ChunkSize::Bytes(4))
+                .set_delimiter(";")
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(
+            chunks[0].as_ref().map_err(io::Error::kind).err(),
+            Some(io::ErrorKind::WouldBlock)
+        );
+        assert_eq!(
+            chunks[1..].iter().flatten().collect::<Vec<_>>(),
+            [b"aaaa;", b"bbbb;", b"cccc;"]
+        );
+    }
+
+    #[test]
+    fn delimiter_t_1() -> io::Result<()> {
+        block_on(async {
+            // the data read ahead is discarded when the position changes
+            let mut file_stream = FileStream::from_reader(Cursor::new(b"aa;bb;cc;dd;"))
+                .await?
+                .set_mode(ChunkSize::Bytes(1))
+                .set_delimiter(";");
+            assert_eq!(file_stream.try_next().await?, Some(b"aa;".to_vec()));
+
+            let chunks = file_stream
+                .set_start_position_bytes(0)
+                .await?
+                .try_collect::<Vec<_>>()
+                .await?;
+            assert_eq!(chunks, [b"aa;", b"bb;", b"cc;", b"dd;"]);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn on_progress_t_0() -> io::Result<()> {
+        block_on(async {
+            let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let data = vec![1; 5_000];
+            FileStream::from_reader_with_size(Cursor::new(&data), data.len())
+                .set_mode(ChunkSize::Bytes(2_000))
+                .on_progress({
+                    let progress = std::sync::Arc::clone(&progress);
+                    move |value: get_chunk::Progress| {
+                        progress
+                            .lock()
+                            .unwrap()
+                            .push((value.bytes_read, value.percent))
+                    }
+                })
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            assert_eq!(
+                *progress.lock().unwrap(),
+                [
+                    (2_000, Some(40.0)),
+                    (4_000, Some(80.0)),
+                    (5_000, Some(100.0))
+                ]
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn send_sync_t_0() {
+        // the stream stays `Send + Sync` with the boxed strategy, memory provider and progress callback
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FileStream<AllowStdIo<File>>>();
+    }
+}
@@ -134,6 +134,19 @@ mod size_format {
             );
             Ok(())
         }
+
+        /// Percent of a source bigger than 100 bytes
+        #[test]
+        pub fn set_start_position_t_2() -> io::Result<()> {
+            let data = (0..10_000).map(|index| index as u8).collect::<Vec<_>>();
+            let chunks = FileIter::from_reader_with_size(io::Cursor::new(&data), data.len())
+                .set_start_position_percent(75.0)?
+                .set_mode(ChunkSize::Bytes(1_000))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(chunks.concat(), data[7_500..]);
+            Ok(())
+        }
     }
 
     #[test]
@@ -320,4 +333,1107 @@ mod size_format {
             Ok(())
         }
     }
+
+    mod delimiter {
+        use super::*;
+
+        #[test]
+        fn delimiter_t_0() -> io::Result<()> {
+            let text = "first line\nsecond\nthird line is longer\nlast";
+            let file_iter = FileIter::try_from(text.as_bytes())?
+                .set_mode(ChunkSize::Bytes(4))
+                .set_delimiter(b"\n".as_slice());
+
+            assert_eq!(
+                file_iter.collect::<io::Result<Vec<_>>>()?,
+                [
+                    b"first line\n".to_vec(),
+                    b"second\n".to_vec(),
+                    b"third line is longer\n".to_vec(),
+                    b"last".to_vec(),
+                ]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn delimiter_t_1() -> io::Result<()> {
+            let text = "a;;bb;;;;ccc;;";
+            let file_iter = FileIter::try_from(text.as_bytes())?
+                .set_mode(ChunkSize::Bytes(2))
+                .set_delimiter(";;");
+
+            assert_eq!(
+                file_iter.collect::<io::Result<Vec<_>>>()?,
+                [
+                    b"a;;".to_vec(),
+                    b"bb;;".to_vec(),
+                    b";;".to_vec(),
+                    b"ccc;;".to_vec(),
+                ]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn delimiter_t_2() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(700.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let mut file_from_chunks = FileTest::default();
+            for chunk in FileIter::new(file_orig.path.as_str())?.set_delimiter(vec![0x0a, 0x0d]) {
+                chunk.map(|data| file_from_chunks.write_bytes_to_file(&data).ok())?;
+            }
+            assert_eq!(file_orig, file_from_chunks);
+            Ok(())
+        }
+
+        /// Delivers at most 4 bytes per read, fails once with `WouldBlock` when the data up to `fail_at` has been read
+        struct Interrupted {
+            data: &'static [u8],
+            fail_at: usize,
+        }
+
+        impl io::Read for Interrupted {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.fail_at == 0 {
+                    self.fail_at = usize::MAX;
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+                let len = self.data.len().min(buf.len()).min(4);
+                buf[..len].copy_from_slice(&self.data[..len]);
+                self.data = &self.data[len..];
+                self.fail_at = self.fail_at.saturating_sub(len);
+                Ok(len)
+            }
+        }
+
+        #[test]
+        fn delimiter_t_3() -> io::Result<()> {
+            // the data read before an error is not lost
+            let reader = Interrupted {
+                data: b"aaaa;bbbb;cccc;",
+                fail_at: 4,
+            };
+            let chunks = FileIter::from_reader_without_size(reader)
+                .set_mode(ChunkSize::Bytes(4))
+                .set_delimiter(";")
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                chunks[0].as_ref().map_err(io::Error::kind).err(),
+                Some(io::ErrorKind::WouldBlock)
+            );
+            assert_eq!(
+                chunks[1..].iter().flatten().collect::<Vec<_>>(),
+                [b"aaaa;", b"bbbb;", b"cccc;"]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn delimiter_t_4() -> io::Result<()> {
+            // the data read ahead is discarded when the position changes
+            let mut file_iter = FileIter::try_from(b"aa;bb;cc;dd;".as_slice())?
+                .set_mode(ChunkSize::Bytes(1))
+                .set_delimiter(";");
+            assert_eq!(file_iter.next().transpose()?, Some(b"aa;".to_vec()));
+
+            let chunks = file_iter
+                .set_start_position_bytes(0)?
+                .collect::<io::Result<Vec<_>>>()?;
+            assert_eq!(chunks, [b"aa;", b"bb;", b"cc;", b"dd;"]);
+            Ok(())
+        }
+    }
+
+    mod line_mode {
+        use super::*;
+
+        #[test]
+        fn line_mode_t_0() -> io::Result<()> {
+            let text = "one\r\ntwo\r\nthree\nfour and more\nfive";
+            let file_iter = FileIter::try_from(text.as_bytes())?
+                .set_mode(ChunkSize::Bytes(12))
+                .set_line_mode();
+
+            assert_eq!(
+                file_iter.collect::<io::Result<Vec<_>>>()?,
+                [
+                    b"one\r\ntwo\r\n".to_vec(),
+                    b"three\n".to_vec(),
+                    b"four and more\n".to_vec(),
+                    b"five".to_vec(),
+                ]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn line_mode_t_1() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(700.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let chunks = FileIter::new(file_orig.path.as_str())?
+                .set_mode(ChunkSize::Bytes(
+                    IECUnit::new(4.0, IECSize::Kibibyte).into(),
+                ))
+                .set_line_mode()
+                .collect::<io::Result<Vec<_>>>()?;
+
+            let mut file_from_chunks = FileTest::default();
+            for (index, chunk) in chunks.iter().enumerate() {
+                assert!(index == chunks.len() - 1 || chunk.ends_with(b"\n"));
+                file_from_chunks.write_bytes_to_file(chunk)?;
+            }
+            assert_eq!(file_orig, file_from_chunks);
+            Ok(())
+        }
+    }
+
+    mod utf8 {
+        use super::*;
+
+        #[test]
+        fn utf8_t_0() -> io::Result<()> {
+            let text = "Привет, мир! 你好 🌍 world";
+            for chunk_size in 1..8 {
+                let chunks = FileIter::try_from(text.as_bytes())?
+                    .set_mode(ChunkSize::Bytes(chunk_size))
+                    .utf8()
+                    .collect::<io::Result<Vec<_>>>()?;
+
+                assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+                assert_eq!(chunks.concat(), text);
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn utf8_t_1() -> io::Result<()> {
+            let mut file_iter = FileIter::try_from([b'a', b'b', 0xF0, 0x9F].as_slice())?
+                .set_mode(ChunkSize::Bytes(2))
+                .utf8();
+
+            assert_eq!(file_iter.next().unwrap()?, "ab");
+            assert_eq!(
+                file_iter.next().unwrap().unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+            assert!(file_iter.next().is_none());
+
+            let mut file_iter = FileIter::try_from([b'a', 0xFF, b'b'].as_slice())?
+                .set_mode(ChunkSize::Bytes(3))
+                .utf8();
+            assert_eq!(
+                file_iter.next().unwrap().unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+            Ok(())
+        }
+    }
+
+    mod content_defined {
+        use super::*;
+        use rand::RngCore;
+
+        const MODE: ChunkSize = ChunkSize::ContentDefined {
+            min: 2 * 1024,
+            avg: 8 * 1024,
+            max: 32 * 1024,
+        };
+
+        #[test]
+        fn content_defined_t_0() -> io::Result<()> {
+            let mut bytes = vec![0; 700 * 1024];
+            rand::thread_rng().fill_bytes(&mut bytes);
+
+            let chunks = FileIter::try_from(bytes.as_slice())?
+                .set_mode(MODE)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            for chunk in &chunks[..chunks.len() - 1] {
+                assert!((2 * 1024..=32 * 1024).contains(&chunk.len()));
+            }
+            assert_eq!(chunks.concat(), bytes);
+            Ok(())
+        }
+
+        #[test]
+        fn content_defined_t_1() -> io::Result<()> {
+            let mut bytes = vec![0; 700 * 1024];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let mut edited = bytes.clone();
+            edited.splice(100..100, b"inserted bytes".iter().copied());
+
+            let chunks = FileIter::try_from(bytes.as_slice())?
+                .set_mode(MODE)
+                .collect::<io::Result<Vec<_>>>()?;
+            let edited_chunks = FileIter::try_from(edited.as_slice())?
+                .set_mode(MODE)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            // only the chunks around the edit are changed
+            let changed = edited_chunks
+                .iter()
+                .filter(|chunk| !chunks.contains(chunk))
+                .count();
+            assert!(changed <= 2, "{changed} chunks changed");
+            Ok(())
+        }
+    }
+
+    mod with_metadata {
+        use super::*;
+
+        #[test]
+        fn with_metadata_t_0() -> io::Result<()> {
+            let bytes: [u8; 13] = [72, 101, 108, 108, 111, 44, 32, 119, 111, 114, 108, 100, 33];
+            let chunks = FileIter::try_from(bytes.as_slice())?
+                .set_start_position_bytes(2)?
+                .set_mode(ChunkSize::Bytes(4))
+                .with_metadata()
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(
+                chunks
+                    .iter()
+                    .map(|chunk| (chunk.index, chunk.offset, chunk.value.as_slice()))
+                    .collect::<Vec<_>>(),
+                [
+                    (0, 2, [108, 108, 111, 44].as_slice()),
+                    (1, 6, [32, 119, 111, 114].as_slice()),
+                    (2, 10, [108, 100, 33].as_slice()),
+                ]
+            );
+            assert!(chunks
+                .iter()
+                .all(|chunk| matches!(chunk.mode, ChunkSize::Bytes(4))));
+            Ok(())
+        }
+    }
+
+    mod from_reader {
+        use super::*;
+        use std::io::{Cursor, Read, Seek, SeekFrom};
+
+        #[test]
+        fn from_reader_t_0() -> io::Result<()> {
+            let bytes: [u8; 13] = [72, 101, 108, 108, 111, 44, 32, 119, 111, 114, 108, 100, 33];
+            let mut cursor = Cursor::new(bytes.as_slice());
+            cursor.seek(SeekFrom::Start(3))?;
+
+            let file_iter = FileIter::from_reader(cursor)?.set_mode(ChunkSize::Bytes(5));
+            assert_eq!(file_iter.get_file_size(), 13.0);
+            assert_eq!(
+                file_iter.collect::<io::Result<Vec<_>>>()?,
+                [
+                    [108, 111, 44, 32, 119].to_vec(),
+                    [111, 114, 108, 100, 33].to_vec(),
+                ]
+            );
+            Ok(())
+        }
+
+        /// A source that is neither a `File` nor a `Cursor<Vec<u8>>`
+        struct Repeat {
+            byte: u8,
+            len: u64,
+            position: u64,
+        }
+
+        impl Read for Repeat {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let count = (buf.len() as u64).min(self.len - self.position) as usize;
+                buf[..count].fill(self.byte);
+                self.position += count as u64;
+                Ok(count)
+            }
+        }
+
+        impl Seek for Repeat {
+            fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+                self.position = match pos {
+                    SeekFrom::Start(offset) => offset,
+                    SeekFrom::End(offset) => (self.len as i64 + offset) as u64,
+                    SeekFrom::Current(offset) => (self.position as i64 + offset) as u64,
+                }
+                .min(self.len);
+                Ok(self.position)
+            }
+        }
+
+        #[test]
+        fn from_reader_t_1() -> io::Result<()> {
+            let repeat = Repeat {
+                byte: 7,
+                len: 10_000,
+                position: 0,
+            };
+            let chunks = FileIter::from_reader_with_size(repeat, 10_000)
+                .set_mode(ChunkSize::Percent(25.0))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(chunks.len(), 4);
+            assert!(chunks.iter().all(|chunk| chunk == &vec![7; 2_500]));
+            Ok(())
+        }
+    }
+
+    mod from_reader_without_size {
+        use super::*;
+        use std::io::Read;
+
+        #[test]
+        fn from_reader_without_size_t_0() -> io::Result<()> {
+            let reader = io::repeat(1).take(200 * 1024);
+
+            let file_iter = FileIter::from_reader_without_size(reader);
+            assert_eq!(file_iter.get_file_size(), 0.0);
+            let chunks = file_iter
+                .set_mode(ChunkSize::Percent(10.0))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            // the percentage cannot be applied, 64 KiB chunks are used instead
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [64 * 1024, 64 * 1024, 64 * 1024, 8 * 1024]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn from_reader_without_size_t_1() -> io::Result<()> {
+            let text = "header\nrow 1\nrow 2\n";
+            let chunks = FileIter::from_reader_without_size(text.as_bytes())
+                .set_mode(ChunkSize::Bytes(3))
+                .set_line_mode()
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(
+                chunks,
+                [
+                    b"header\n".to_vec(),
+                    b"row 1\n".to_vec(),
+                    b"row 2\n".to_vec()
+                ]
+            );
+            Ok(())
+        }
+    }
+
+    mod read_into {
+        use super::*;
+
+        #[test]
+        fn read_into_t_0() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(500.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let mut file_from_chunks = FileTest::default();
+            let mut file_iter = FileIter::new(file_orig.path.as_str())?;
+            let mut buffer = Vec::new();
+            while file_iter.read_into(&mut buffer)? != 0 {
+                file_from_chunks.write_bytes_to_file(&buffer)?;
+            }
+            assert!(file_iter.is_read_complete());
+            assert_eq!(file_orig, file_from_chunks);
+            Ok(())
+        }
+
+        #[test]
+        fn read_into_t_1() -> io::Result<()> {
+            let text = "first\nsecond\nthird\n";
+            let mut file_iter = FileIter::from_reader_without_size(text.as_bytes())
+                .set_mode(ChunkSize::Bytes(8))
+                .set_line_mode();
+
+            // the allocation of the first chunk is reused by the following ones
+            let mut buffer = Vec::with_capacity(64);
+            let data = buffer.as_ptr();
+            let mut lines = Vec::new();
+            while file_iter.read_into(&mut buffer)? != 0 {
+                assert_eq!(buffer.as_ptr(), data);
+                lines.push(String::from_utf8(buffer.clone()).unwrap());
+            }
+            assert_eq!(lines, ["first\n", "second\n", "third\n"]);
+            assert!(buffer.is_empty());
+            Ok(())
+        }
+    }
+
+    mod prefetch {
+        use super::*;
+        use std::io::Read;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::thread;
+        use std::time::{Duration, Instant};
+
+        #[test]
+        fn prefetch_t_0() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(500.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let mut file_from_chunks = FileTest::default();
+            for chunk in FileIter::new(file_orig.path.as_str())?
+                .set_mode(ChunkSize::Bytes(16 * 1024))
+                .prefetch(4)
+            {
+                let chunk = chunk?;
+                assert!(chunk.len() <= 16 * 1024);
+                file_from_chunks.write_bytes_to_file(&chunk)?;
+            }
+            assert_eq!(file_orig, file_from_chunks);
+            Ok(())
+        }
+
+        #[test]
+        fn prefetch_t_1() -> io::Result<()> {
+            let data = (0..100_000).map(|index| index as u8).collect::<Vec<_>>();
+            // the background thread is stopped when the iterator is dropped early
+            let chunks = FileIter::from_reader_without_size(io::Cursor::new(data.clone()))
+                .set_mode(ChunkSize::Bytes(1_000))
+                .prefetch(0)
+                .take(3)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(chunks.concat(), data[..3_000]);
+            Ok(())
+        }
+
+        /// Counts the bytes pulled from the source
+        struct Counting {
+            data: io::Cursor<Vec<u8>>,
+            read: Arc<AtomicUsize>,
+        }
+
+        impl Read for Counting {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let read = self.data.read(buf)?;
+                self.read.fetch_add(read, Ordering::SeqCst);
+                Ok(read)
+            }
+        }
+
+        #[test]
+        fn prefetch_t_2() -> io::Result<()> {
+            // no more than `depth` chunks are read ahead of the one being processed
+            const CHUNK: usize = 64 * 1024;
+            for depth in [1, 2] {
+                let read = Arc::new(AtomicUsize::new(0));
+                let mut chunks = FileIter::from_reader_without_size(Counting {
+                    data: io::Cursor::new(vec![1; 10 * CHUNK]),
+                    read: Arc::clone(&read),
+                })
+                .set_mode(ChunkSize::Bytes(CHUNK))
+                .prefetch(depth);
+                assert_eq!(
+                    chunks.next().transpose()?.map(|chunk| chunk.len()),
+                    Some(CHUNK)
+                );
+
+                let started = Instant::now();
+                while read.load(Ordering::SeqCst) < (depth + 1) * CHUNK
+                    && started.elapsed() < Duration::from_secs(5)
+                {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                thread::sleep(Duration::from_millis(100));
+                // the source is read through an 8 KiB buffer
+                let read = read.load(Ordering::SeqCst);
+                assert!(
+                    ((depth + 1) * CHUNK..=(depth + 1) * CHUNK + 8 * 1024).contains(&read),
+                    "{depth}: {read}"
+                );
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    mod par_chunks {
+        use super::*;
+        use rayon::prelude::*;
+
+        #[test]
+        fn par_chunks_t_0() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(500.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let ranges = FileIter::new(file_orig.path.as_str())?
+                .set_mode(ChunkSize::Bytes(64 * 1024))
+                .set_start_position_bytes(1_000)?
+                .par_chunks()
+                .collect::<io::Result<Vec<_>>>()?;
+
+            // the order of an indexed parallel iterator is preserved
+            assert_eq!(
+                ranges.iter().map(|(offset, _)| *offset).collect::<Vec<_>>(),
+                (0..8)
+                    .map(|index| 1_000 + index * 64 * 1024)
+                    .collect::<Vec<_>>()
+            );
+            let data = std::fs::read(file_orig.path.as_str())?;
+            assert_eq!(
+                ranges
+                    .into_iter()
+                    .flat_map(|(_, data)| data)
+                    .collect::<Vec<_>>(),
+                data[1_000..]
+            );
+            Ok(())
+        }
+    }
+
+    mod set_strategy {
+        use super::*;
+        use get_chunk::ChunkStrategy;
+
+        /// Doubles the chunk size, starting from 1 KiB
+        struct Doubling;
+
+        impl ChunkStrategy for Doubling {
+            fn next_chunk_size(&mut self, prev: f64, _: f64, _: Option<f64>, _: f64) -> f64 {
+                if prev > 0.0 {
+                    prev * 2.0
+                } else {
+                    1024.0
+                }
+            }
+        }
+
+        #[test]
+        fn set_strategy_t_0() -> io::Result<()> {
+            let data = vec![1; 20 * 1024];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_strategy(Doubling)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [1024, 2048, 4096, 8192, 5120]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn set_strategy_t_1() -> io::Result<()> {
+            // the strategy is used only in the Auto mode
+            let data = vec![1; 10_000];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_strategy(Doubling)
+                .set_mode(ChunkSize::Bytes(4_000))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [4_000, 4_000, 2_000]
+            );
+            Ok(())
+        }
+    }
+
+    mod set_memory_provider {
+        use super::*;
+        use get_chunk::{FixedMemory, MemoryProvider, SystemMemory};
+
+        /// Simulates memory pressure: the available memory halves before every chunk
+        struct Pressure(f64);
+
+        impl MemoryProvider for Pressure {
+            fn available_memory(&mut self, _: bool) -> f64 {
+                self.0 /= 2.0;
+                self.0
+            }
+        }
+
+        #[test]
+        fn set_memory_provider_t_0() -> io::Result<()> {
+            let data = vec![1; 30_000];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_memory_provider(FixedMemory(10_000))
+                .set_mode(ChunkSize::Bytes(100_000))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            // 85% of the budget
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [8_500, 8_500, 8_500, 4_500]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn set_memory_provider_t_1() -> io::Result<()> {
+            let data = vec![1; 30_000];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_memory_provider(Pressure(80_000.0))
+                .set_mode(ChunkSize::Bytes(100_000))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [30_000]);
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_memory_provider(Pressure(40_000.0))
+                .set_mode(ChunkSize::Bytes(100_000))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [17_000, 8_500, 4_250, 250]
+            );
+            Ok(())
+        }
+
+        /// Creates fake cgroup files in a temporary directory and returns the sizes of the chunks read with them
+        fn cgroup_chunks(files: &[(&str, &str)]) -> io::Result<Vec<usize>> {
+            let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+            std::fs::create_dir_all(&root)?;
+            for (path, value) in files {
+                let path = root.join(path);
+                std::fs::create_dir_all(path.parent().unwrap())?;
+                std::fs::write(path, value)?;
+            }
+            let chunks = FileIter::try_from(vec![1; 30_000])?
+                .set_memory_provider(SystemMemory::new().with_cgroup_root(&root))
+                .set_mode(ChunkSize::Bytes(100_000))
+                .map(|chunk| chunk.map(|chunk| chunk.len()))
+                .collect::<io::Result<Vec<_>>>();
+            std::fs::remove_dir_all(root)?;
+            chunks
+        }
+
+        #[test]
+        fn cgroup_t_0() -> io::Result<()> {
+            // v2
+            assert_eq!(
+                cgroup_chunks(&[("memory.max", "20000\n"), ("memory.current", "10000\n")])?,
+                [8_500, 8_500, 8_500, 4_500]
+            );
+            // v1
+            assert_eq!(
+                cgroup_chunks(&[
+                    ("memory/memory.limit_in_bytes", "30000\n"),
+                    ("memory/memory.usage_in_bytes", "10000\n")
+                ])?,
+                [17_000, 13_000]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn cgroup_t_1() -> io::Result<()> {
+            // no limit, only the RAM of the system is taken into account
+            assert_eq!(
+                cgroup_chunks(&[("memory.max", "max\n"), ("memory.current", "10000\n")])?,
+                [30_000]
+            );
+            assert_eq!(cgroup_chunks(&[])?, [30_000]);
+            Ok(())
+        }
+
+        #[test]
+        fn cgroup_t_2() -> io::Result<()> {
+            // the usage is close to the limit, but most of it is the page cache that can be reclaimed
+            // v2
+            assert_eq!(
+                cgroup_chunks(&[
+                    ("memory.max", "40000\n"),
+                    ("memory.current", "39000\n"),
+                    (
+                        "memory.stat",
+                        "anon 9000\nfile 30000\ninactive_file 29000\n"
+                    )
+                ])?,
+                [25_500, 4_500]
+            );
+            // v1
+            assert_eq!(
+                cgroup_chunks(&[
+                    ("memory/memory.limit_in_bytes", "40000\n"),
+                    ("memory/memory.usage_in_bytes", "39000\n"),
+                    (
+                        "memory/memory.stat",
+                        "inactive_file 1000\ntotal_inactive_file 29000\n"
+                    )
+                ])?,
+                [25_500, 4_500]
+            );
+            Ok(())
+        }
+    }
+
+    mod chunk_bounds {
+        use super::*;
+        use get_chunk::FixedMemory;
+
+        #[test]
+        fn chunk_bounds_t_0() -> io::Result<()> {
+            // without the bounds the first chunk is 0.1% of the file (10 bytes)
+            let data = vec![1; 10 * 1024];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_min_chunk(IECUnit::new(1.0, IECSize::Kibibyte).into())
+                .set_max_chunk(IECUnit::new(1.0, IECSize::Kibibyte).into())
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [1024; 10]);
+            Ok(())
+        }
+
+        #[test]
+        fn chunk_bounds_t_1() -> io::Result<()> {
+            let data = vec![1; 100_000];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_max_chunk(4_000)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert!(chunks.iter().all(|chunk| chunk.len() <= 4_000));
+            assert_eq!(chunks.concat(), data);
+            Ok(())
+        }
+
+        #[test]
+        fn chunk_bounds_t_2() -> io::Result<()> {
+            // the RAM limit takes precedence over the minimum
+            let data = vec![1; 30_000];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_memory_provider(FixedMemory(10_000))
+                .set_min_chunk(20_000)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [8_500, 8_500, 8_500, 4_500]
+            );
+
+            // the bounds are used only in the Auto mode
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_min_chunk(20_000)
+                .set_mode(ChunkSize::Bytes(12_000))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [12_000, 12_000, 6_000]
+            );
+            Ok(())
+        }
+    }
+
+    mod auto_strategy {
+        use get_chunk::{AutoStrategy, ChunkStrategy};
+
+        /// Runs the strategy against a reader model, which returns the read speed for a chunk size
+        fn run(
+            strategy: &mut AutoStrategy,
+            mut chunk_size: f64,
+            iterations: usize,
+            model: impl Fn(f64) -> f64,
+        ) -> Vec<f64> {
+            (0..iterations)
+                .map(|_| {
+                    chunk_size =
+                        strategy.next_chunk_size(chunk_size, model(chunk_size), Some(1e9), 1e12);
+                    chunk_size
+                })
+                .collect()
+        }
+
+        /// 1 ms per read, the read speed drops for chunks bigger than `optimum`
+        fn with_optimum(optimum: f64) -> impl Fn(f64) -> f64 {
+            move |size| size / (0.001 + 0.001 * (size / optimum).powi(2))
+        }
+
+        #[test]
+        fn auto_strategy_t_0() {
+            // only a fixed latency per read: bigger chunks are always faster
+            let mut strategy = AutoStrategy::new();
+            assert_eq!(strategy.next_chunk_size(-1.0, 0.0, Some(1e9), 1e12), 1e6);
+            let sizes = run(&mut strategy, 1e6, 20, |size| size / 0.001);
+            assert!(sizes
+                .windows(2)
+                .all(|pair| (pair[1] / pair[0] - 1.15).abs() < 1e-9));
+
+            // the speed is unknown, the size is kept
+            assert_eq!(
+                strategy.next_chunk_size(5_000.0, 0.0, Some(1e9), 1e12),
+                5_000.0
+            );
+            // limited by the RAM
+            assert_eq!(
+                strategy.next_chunk_size(5_000.0, 1e9, Some(1e9), 4_000.0),
+                3_400.0
+            );
+        }
+
+        #[test]
+        fn auto_strategy_t_1() {
+            // the size converges to the fastest one and follows it when the reader becomes slower or faster
+            let mut strategy = AutoStrategy::new();
+            let mut chunk_size = 100.0;
+            for optimum in [10_000.0, 2_500.0, 40_000.0] {
+                let sizes = run(&mut strategy, chunk_size, 100, with_optimum(optimum));
+                assert!(
+                    sizes[80..]
+                        .iter()
+                        .all(|size| (size / optimum - 1.0).abs() < 0.05),
+                    "{optimum}: {:?}",
+                    &sizes[80..]
+                );
+                chunk_size = sizes[99];
+            }
+        }
+
+        /// Simulated reader with a latency of 1-1.3 ms per read (pseudo-random), every 10th read is 3 times slower
+        struct SimulatedReader {
+            state: u64,
+            reads: usize,
+        }
+
+        impl SimulatedReader {
+            /// Returns the read speed for a chunk size
+            fn read(&mut self, size: f64) -> f64 {
+                // xorshift
+                self.state ^= self.state << 13;
+                self.state ^= self.state >> 7;
+                self.state ^= self.state << 17;
+                let latency = 0.001 * (1.0 + 0.3 * (self.state % 1_000) as f64 / 1_000.0);
+                self.reads += 1;
+                if self.reads == 10 {
+                    self.reads = 0;
+                    return size / (latency * 3.0);
+                }
+                size / latency
+            }
+        }
+
+        #[test]
+        fn auto_strategy_t_2() {
+            // the first chunk is 0.1% of the file, the latency of the reader makes the chunks grow despite the noise
+            let mut strategy = AutoStrategy::new();
+            let mut reader = SimulatedReader { state: 1, reads: 0 };
+            let mut chunk_size = strategy.next_chunk_size(-1.0, 0.0, Some(200_000.0), 1e12);
+            assert_eq!(chunk_size, 200.0);
+
+            let mut sizes = Vec::new();
+            let mut position = 0.0;
+            while position < 200_000.0 {
+                position += chunk_size;
+                sizes.push(chunk_size);
+                chunk_size = strategy.next_chunk_size(
+                    chunk_size,
+                    reader.read(chunk_size),
+                    Some(200_000.0),
+                    1e12,
+                );
+            }
+            assert!(sizes.iter().copied().fold(0.0, f64::max) > 20_000.0);
+        }
+    }
+
+    mod target_duration {
+        use super::*;
+        use std::io::{Read, Seek};
+        use std::thread;
+        use std::time::{Duration, Instant};
+
+        /// Delivers the data at a fixed speed
+        struct Throttled {
+            data: io::Cursor<Vec<u8>>,
+            start: Instant,
+            bytes_per_second: f64,
+        }
+
+        impl Read for Throttled {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let read = self.data.read(buf)?;
+                let due = self.start
+                    + Duration::from_secs_f64(self.data.position() as f64 / self.bytes_per_second);
+                thread::sleep(due.saturating_duration_since(Instant::now()));
+                Ok(read)
+            }
+        }
+
+        impl Seek for Throttled {
+            fn seek(&mut self, position: io::SeekFrom) -> io::Result<u64> {
+                self.data.seek(position)
+            }
+        }
+
+        #[test]
+        fn target_duration_t_0() -> io::Result<()> {
+            // 1 MB/s, 20 ms per chunk
+            let data = vec![1; 300_000];
+            let reader = Throttled {
+                data: io::Cursor::new(data.clone()),
+                start: Instant::now(),
+                bytes_per_second: 1_000_000.0,
+            };
+            let chunks = FileIter::from_reader_with_size(reader, data.len())
+                .set_mode(ChunkSize::TargetDuration(Duration::from_millis(20)))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            // the size doubles at most from 0.1% of the file, then stays around 20 KB
+            assert_eq!(
+                chunks[..3].iter().map(Vec::len).collect::<Vec<_>>(),
+                [300, 600, 1_200]
+            );
+            assert!(
+                chunks[10..chunks.len() - 1]
+                    .iter()
+                    .all(|chunk| (14_000..=26_000).contains(&chunk.len())),
+                "{:?}",
+                chunks.iter().map(Vec::len).collect::<Vec<_>>()
+            );
+            assert_eq!(chunks.concat(), data);
+            Ok(())
+        }
+
+        #[test]
+        fn target_duration_t_1() -> io::Result<()> {
+            // bounded like the Auto mode
+            let data = vec![1; 10_000];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_mode(ChunkSize::TargetDuration(Duration::from_secs(1)))
+                .set_min_chunk(2_000)
+                .set_max_chunk(2_000)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [2_000; 5]);
+            Ok(())
+        }
+    }
+
+    mod rate_limit {
+        use super::*;
+        use std::time::{Duration, Instant};
+
+        #[test]
+        fn rate_limit_t_0() -> io::Result<()> {
+            // 5 chunks of 10 KiB at 100 KiB/s, the reads after the first one wait 100 ms each
+            let data = vec![1; 50 * 1024];
+            let timer = Instant::now();
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_mode(ChunkSize::Bytes(10 * 1024))
+                .set_rate_limit(IECUnit::new(100.0, IECSize::Kibibyte).into())
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert!(timer.elapsed() >= Duration::from_millis(400));
+            assert_eq!(chunks.concat(), data);
+            Ok(())
+        }
+
+        #[test]
+        fn rate_limit_t_1() -> io::Result<()> {
+            // the time spent on processing a chunk counts towards the limit
+            let data = vec![1; 20 * 1024];
+            let mut file_iter = FileIter::try_from(data.as_slice())?
+                .set_mode(ChunkSize::Bytes(10 * 1024))
+                .set_rate_limit(100 * 1024);
+            file_iter.next().transpose()?;
+            std::thread::sleep(Duration::from_millis(100));
+
+            let timer = Instant::now();
+            file_iter.next().transpose()?;
+            assert!(timer.elapsed() < Duration::from_millis(50));
+
+            // `0` removes the limit
+            let timer = Instant::now();
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_mode(ChunkSize::Bytes(1024))
+                .set_rate_limit(1)
+                .set_rate_limit(0)
+                .collect::<io::Result<Vec<_>>>()?;
+            assert_eq!(chunks.len(), 20);
+            assert!(timer.elapsed() < Duration::from_secs(1));
+            Ok(())
+        }
+    }
+
+    mod on_progress {
+        use super::*;
+        use get_chunk::Progress;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        #[test]
+        fn on_progress_t_0() -> io::Result<()> {
+            let progress = Arc::new(Mutex::new(Vec::<Progress>::new()));
+            let data = vec![1; 5_000];
+            let chunks = FileIter::try_from(data.as_slice())?
+                .set_mode(ChunkSize::Bytes(1_000))
+                .on_progress({
+                    let progress = Arc::clone(&progress);
+                    move |value| progress.lock().unwrap().push(value)
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+
+            let progress = progress.lock().unwrap();
+            assert_eq!(progress.len(), chunks.len());
+            assert_eq!(
+                progress
+                    .iter()
+                    .map(|value| value.bytes_read)
+                    .collect::<Vec<_>>(),
+                [1_000, 2_000, 3_000, 4_000, 5_000]
+            );
+            assert_eq!(
+                progress
+                    .iter()
+                    .map(|value| value.percent)
+                    .collect::<Vec<_>>(),
+                [Some(20.0), Some(40.0), Some(60.0), Some(80.0), Some(100.0)]
+            );
+            assert!(progress
+                .iter()
+                .all(|value| value.total_bytes == Some(5_000)));
+            assert!(progress[..4].iter().all(|value| value.eta.is_some()));
+            assert_eq!(progress[4].eta, Some(Duration::ZERO));
+            Ok(())
+        }
+
+        #[test]
+        fn on_progress_t_1() -> io::Result<()> {
+            // the size is unknown
+            let progress = Arc::new(Mutex::new(Vec::<Progress>::new()));
+            FileIter::from_reader_without_size(io::Cursor::new(vec![1; 5_000]))
+                .set_mode(ChunkSize::Bytes(2_000))
+                .on_progress({
+                    let progress = Arc::clone(&progress);
+                    move |value| progress.lock().unwrap().push(value)
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+
+            let progress = progress.lock().unwrap();
+            assert_eq!(
+                progress
+                    .iter()
+                    .map(|value| value.bytes_read)
+                    .collect::<Vec<_>>(),
+                [2_000, 4_000, 5_000]
+            );
+            assert!(progress.iter().all(|value| value.total_bytes.is_none()
+                && value.percent.is_none()
+                && value.eta.is_none()
+                && value.average_bytes_per_second > 0.0));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn send_sync_t_0() {
+        // the iterator stays `Send + Sync` with the boxed strategy, memory provider and progress callback
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FileIter<std::fs::File>>();
+    }
 }
@@ -0,0 +1,104 @@
+#[cfg(all(feature = "size_format", feature = "mmap"))]
+mod temp_files;
+
+#[cfg(all(feature = "size_format", feature = "mmap"))]
+mod size_format {
+    use super::*;
+    use get_chunk::data_size_format::iec::{IECSize, IECUnit};
+    use get_chunk::mmap::FileMmap;
+    use get_chunk::ChunkSize;
+    use std::io;
+    use temp_files::{FileTest, FILE_TEST};
+
+    mod set_mode_tests {
+        use super::*;
+
+        /// Bytes
+        #[test]
+        pub fn set_mode_t_0() -> io::Result<()> {
+            let chunk_size = 150.0;
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(960.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let mut file_mmap = unsafe { FileMmap::new(file_orig.path.as_str())? }.set_mode(
+                ChunkSize::Bytes(IECUnit::new(chunk_size, IECSize::Kibibyte).into()),
+            );
+
+            let mut elements = file_mmap.chunks().collect::<Vec<_>>();
+            elements.pop();
+
+            for chunk in elements {
+                assert_eq!(
+                    chunk.len(),
+                    IECUnit::new(chunk_size, IECSize::Kibibyte).get_values().1 as usize
+                );
+            }
+            assert!(file_mmap.is_read_complete());
+            Ok(())
+        }
+
+        /// Auto
+        #[test]
+        pub fn set_mode_t_1() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(700.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let mut file_from_chunks = FileTest::default();
+            let mut file_mmap = unsafe { FileMmap::new(file_orig.path.as_str())? };
+            for chunk in file_mmap.chunks() {
+                file_from_chunks.write_bytes_to_file(chunk)?;
+            }
+            assert_eq!(file_orig, file_from_chunks);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn set_start_position_t_0() -> io::Result<()> {
+        let file = FileTest::create_with_text(&FILE_TEST, &"Hello world :D, I'm a test file!")?;
+        let mut file_mmap = unsafe { FileMmap::new(file.path.as_str())? }
+            .set_start_position_bytes(6)
+            .set_mode(ChunkSize::Bytes(5));
+
+        assert_eq!(file_mmap.get_file_size(), 32.0);
+        assert_eq!(file_mmap.chunks().next(), Some(b"world".as_slice()));
+        // a new iterator continues from the same position
+        assert_eq!(file_mmap.chunks().next(), Some(b" :D, ".as_slice()));
+        Ok(())
+    }
+
+    #[test]
+    fn on_progress_t_0() -> io::Result<()> {
+        let file = FileTest::create_with_text(&FILE_TEST, &"Hello world :D, I'm a test file!")?;
+        let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut file_mmap = unsafe { FileMmap::new(file.path.as_str())? }
+            .set_mode(ChunkSize::Bytes(16))
+            .on_progress({
+                let progress = std::sync::Arc::clone(&progress);
+                move |value: get_chunk::Progress| {
+                    progress
+                        .lock()
+                        .unwrap()
+                        .push((value.bytes_read, value.percent))
+                }
+            });
+
+        assert_eq!(file_mmap.chunks().count(), 2);
+        assert_eq!(
+            *progress.lock().unwrap(),
+            [(16, Some(50.0)), (32, Some(100.0))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn send_sync_t_0() {
+        // the mapping stays `Send + Sync` with the boxed strategy, memory provider and progress callback
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FileMmap>();
+    }
+}
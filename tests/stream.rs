@@ -151,6 +151,22 @@ mod size_format {
 
             Ok(())
         }
+
+        /// Percent of a source bigger than 100 bytes
+        #[tokio::test]
+        pub async fn set_start_position_t_2() -> io::Result<()> {
+            let data = (0..10_000).map(|index| index as u8).collect::<Vec<_>>();
+            let chunks =
+                FileStream::from_reader_with_size(std::io::Cursor::new(data.clone()), data.len())
+                    .set_start_position_percent(75.0)
+                    .await?
+                    .set_mode(ChunkSize::Bytes(1_000))
+                    .collect::<io::Result<Vec<_>>>()
+                    .await?;
+
+            assert_eq!(chunks.concat(), data[7_500..]);
+            Ok(())
+        }
     }
 
     #[tokio::test]
@@ -327,4 +343,626 @@ mod size_format {
             Ok(())
         }
     }
+
+    mod delimiter {
+        use super::*;
+
+        #[tokio::test]
+        async fn delimiter_t_0() -> io::Result<()> {
+            let text = "first line\nsecond\nthird line is longer\nlast";
+            let file_stream = FileStream::try_from_data(text.as_bytes().to_vec())
+                .await?
+                .set_mode(ChunkSize::Bytes(4))
+                .set_delimiter(b"\n".as_slice());
+
+            assert_eq!(
+                file_stream.collect::<io::Result<Vec<_>>>().await?,
+                [
+                    b"first line\n".to_vec(),
+                    b"second\n".to_vec(),
+                    b"third line is longer\n".to_vec(),
+                    b"last".to_vec(),
+                ]
+            );
+            Ok(())
+        }
+
+        /// Delivers at most 4 bytes per read, fails once with `WouldBlock` when the data up to `fail_at` has been read
+        struct Interrupted {
+            data: &'static [u8],
+            fail_at: usize,
+        }
+
+        impl tokio::io::AsyncRead for Interrupted {
+            fn poll_read(
+                mut self: std::pin::Pin<&mut Self>,
+                _: &mut std::task::Context<'_>,
+                buf: &mut tokio::io::ReadBuf<'_>,
+            ) -> std::task::Poll<io::Result<()>> {
+                if self.fail_at == 0 {
+                    self.fail_at = usize::MAX;
+                    return std::task::Poll::Ready(Err(io::ErrorKind::WouldBlock.into()));
+                }
+                let len = self.data.len().min(buf.remaining()).min(4);
+                buf.put_slice(&self.data[..len]);
+                self.data = &self.data[len..];
+                self.fail_at = self.fail_at.saturating_sub(len);
+                std::task::Poll::Ready(Ok(()))
+            }
+        }
+
+        #[tokio::test]
+        async fn delimiter_t_1() -> io::Result<()> {
+            // the data read before an error is not lost
+            let reader = Interrupted {
+                data: b"aaaa;bbbb;cccc;",
+                fail_at: 4,
+            };
+            let chunks = FileStream::from_reader_without_size(reader)
+                .set_mode(ChunkSize::Bytes(4))
+                .set_delimiter(";")
+                .collect::<Vec<_>>()
+                .await;
+
+            assert_eq!(
+                chunks[0].as_ref().map_err(io::Error::kind).err(),
+                Some(io::ErrorKind::WouldBlock)
+            );
+            assert_eq!(
+                chunks[1..].iter().flatten().collect::<Vec<_>>(),
+                [b"aaaa;", b"bbbb;", b"cccc;"]
+            );
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn delimiter_t_2() -> io::Result<()> {
+            // the data read ahead is discarded when the position changes
+            let mut file_stream = FileStream::try_from_data(b"aa;bb;cc;dd;".to_vec())
+                .await?
+                .set_mode(ChunkSize::Bytes(1))
+                .set_delimiter(";");
+            assert_eq!(file_stream.next().await.transpose()?, Some(b"aa;".to_vec()));
+
+            let chunks = file_stream
+                .set_start_position_bytes(0)
+                .await?
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+            assert_eq!(chunks, [b"aa;", b"bb;", b"cc;", b"dd;"]);
+            Ok(())
+        }
+    }
+
+    mod line_mode {
+        use super::*;
+
+        #[tokio::test]
+        async fn line_mode_t_0() -> io::Result<()> {
+            let text = "one\r\ntwo\r\nthree\nfour and more\nfive";
+            let file_stream = FileStream::try_from_data(text.as_bytes().to_vec())
+                .await?
+                .set_mode(ChunkSize::Bytes(12))
+                .set_line_mode();
+
+            assert_eq!(
+                file_stream.collect::<io::Result<Vec<_>>>().await?,
+                [
+                    b"one\r\ntwo\r\n".to_vec(),
+                    b"three\n".to_vec(),
+                    b"four and more\n".to_vec(),
+                    b"five".to_vec(),
+                ]
+            );
+            Ok(())
+        }
+    }
+
+    mod utf8 {
+        use super::*;
+
+        #[tokio::test]
+        async fn utf8_t_0() -> io::Result<()> {
+            let text = "Привет, мир! 你好 🌍 world";
+            for chunk_size in 1..8 {
+                let chunks = FileStream::try_from_data(text.as_bytes().to_vec())
+                    .await?
+                    .set_mode(ChunkSize::Bytes(chunk_size))
+                    .utf8()
+                    .collect::<io::Result<Vec<_>>>()
+                    .await?;
+
+                assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+                assert_eq!(chunks.concat(), text);
+            }
+            Ok(())
+        }
+    }
+
+    mod content_defined {
+        use super::*;
+
+        #[tokio::test]
+        async fn content_defined_t_0() -> io::Result<()> {
+            let bytes = (0..300 * 1024)
+                .map(|index: u32| (index.wrapping_mul(2_654_435_761) >> 13) as u8)
+                .collect::<Vec<_>>();
+            let mode = ChunkSize::ContentDefined {
+                min: 1024,
+                avg: 4 * 1024,
+                max: 16 * 1024,
+            };
+
+            let chunks = FileStream::try_from_data(bytes.clone())
+                .await?
+                .set_mode(mode)
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+            let expected = get_chunk::iterator::FileIter::try_from(bytes.as_slice())?
+                .set_mode(mode)
+                .collect::<io::Result<Vec<_>>>()?;
+
+            assert_eq!(chunks, expected);
+            assert_eq!(chunks.concat(), bytes);
+            Ok(())
+        }
+    }
+
+    mod with_metadata {
+        use super::*;
+
+        #[tokio::test]
+        async fn with_metadata_t_0() -> io::Result<()> {
+            let bytes = [72, 101, 108, 108, 111, 44, 32, 119, 111, 114, 108, 100, 33].to_vec();
+            let chunks = FileStream::try_from_data(bytes)
+                .await?
+                .set_start_position_bytes(2)
+                .await?
+                .set_mode(ChunkSize::Bytes(4))
+                .with_metadata()
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(
+                chunks
+                    .iter()
+                    .map(|chunk| (chunk.index, chunk.offset, chunk.value.as_slice()))
+                    .collect::<Vec<_>>(),
+                [
+                    (0, 2, [108, 108, 111, 44].as_slice()),
+                    (1, 6, [32, 119, 111, 114].as_slice()),
+                    (2, 10, [108, 100, 33].as_slice()),
+                ]
+            );
+            assert!(chunks
+                .iter()
+                .all(|chunk| matches!(chunk.mode, ChunkSize::Bytes(4))));
+            Ok(())
+        }
+    }
+
+    mod from_reader {
+        use super::*;
+        use std::io::Cursor;
+
+        #[tokio::test]
+        async fn from_reader_t_0() -> io::Result<()> {
+            let data = b"0123456789abcdef";
+            let mut cursor = Cursor::new(data.as_slice());
+            cursor.set_position(6);
+
+            let file_stream = FileStream::from_reader(cursor).await?;
+            assert_eq!(file_stream.get_file_size(), 16.0);
+            let chunks = file_stream
+                .set_mode(ChunkSize::Bytes(4))
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(chunks, [b"6789".to_vec(), b"abcd".to_vec(), b"ef".to_vec()]);
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn from_reader_t_1() -> io::Result<()> {
+            let data = (0..10_000).map(|index| index as u8).collect::<Vec<_>>();
+            let chunks =
+                FileStream::from_reader_with_size(Cursor::new(data.as_slice()), data.len())
+                    .set_mode(ChunkSize::Percent(25.0))
+                    .collect::<io::Result<Vec<_>>>()
+                    .await?;
+
+            assert_eq!(chunks.len(), 4);
+            assert!(chunks.iter().all(|chunk| chunk.len() == 2_500));
+            assert_eq!(chunks.concat(), data);
+            Ok(())
+        }
+    }
+
+    mod from_reader_without_size {
+        use super::*;
+        use tokio::io::AsyncWriteExt;
+
+        #[tokio::test]
+        async fn from_reader_without_size_t_0() -> io::Result<()> {
+            let (mut writer, reader) = tokio::io::duplex(1024);
+            tokio::spawn(async move {
+                for _ in 0..100 {
+                    writer.write_all(&[5; 1000]).await?;
+                }
+                io::Result::Ok(())
+            });
+
+            let chunks = FileStream::from_reader_without_size(reader)
+                .set_mode(ChunkSize::Bytes(30_000))
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [30_000, 30_000, 30_000, 10_000]
+            );
+            assert!(chunks.concat().iter().all(|byte| *byte == 5));
+            Ok(())
+        }
+    }
+
+    mod poll_in_place {
+        use super::*;
+        use tokio::io::AsyncWriteExt;
+
+        #[tokio::test(flavor = "current_thread")]
+        async fn poll_in_place_t_0() -> io::Result<()> {
+            // a borrowed source, the stream does not require `'static`
+            let data = (0..10_000).map(|index| index as u8).collect::<Vec<_>>();
+            let chunks = FileStream::from_reader_without_size(data.as_slice())
+                .set_mode(ChunkSize::Bytes(3_000))
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [3_000, 3_000, 3_000, 1_000]
+            );
+            assert_eq!(chunks.concat(), data);
+            Ok(())
+        }
+
+        #[tokio::test(flavor = "current_thread")]
+        async fn poll_in_place_t_1() -> io::Result<()> {
+            let (mut writer, reader) = tokio::io::duplex(1024);
+            let mut file_stream = FileStream::from_reader_without_size(reader)
+                .set_mode(ChunkSize::Bytes(4))
+                .set_delimiter(b";".as_slice());
+
+            // the read is cancelled while it waits for the rest of the record
+            writer.write_all(b"abc").await?;
+            tokio::select! {
+                biased;
+                _ = file_stream.next() => panic!("the record is not complete"),
+                _ = tokio::task::yield_now() => {}
+            }
+
+            writer.write_all(b"def;gh;").await?;
+            drop(writer);
+            let chunks = file_stream.collect::<io::Result<Vec<_>>>().await?;
+            assert_eq!(chunks, [b"abcdef;".to_vec(), b"gh;".to_vec()]);
+            Ok(())
+        }
+    }
+
+    mod prefetch {
+        use super::*;
+        use std::pin::Pin;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::task::{Context, Poll};
+        use std::time::{Duration, Instant};
+        use tokio::io::{AsyncRead, ReadBuf};
+
+        #[tokio::test]
+        async fn prefetch_t_0() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(500.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let mut file_from_chunks = FileTest::default();
+            let mut file_stream = FileStream::new(file_orig.path.as_str())
+                .await?
+                .set_mode(ChunkSize::Bytes(16 * 1024))
+                .prefetch(4);
+            while let Some(chunk) = file_stream.try_next().await? {
+                assert!(chunk.len() <= 16 * 1024);
+                file_from_chunks.write_bytes_to_file(&chunk)?;
+            }
+            assert_eq!(file_orig, file_from_chunks);
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn prefetch_t_1() -> io::Result<()> {
+            let data = (0..100_000).map(|index| index as u8).collect::<Vec<_>>();
+            // the background task is stopped when the stream is dropped early
+            let chunks = FileStream::from_reader_without_size(std::io::Cursor::new(data.clone()))
+                .set_mode(ChunkSize::Bytes(1_000))
+                .prefetch(0)
+                .take(3)
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(chunks.concat(), data[..3_000]);
+            Ok(())
+        }
+
+        /// Counts the bytes pulled from the source
+        struct Counting {
+            data: std::io::Cursor<Vec<u8>>,
+            read: Arc<AtomicUsize>,
+        }
+
+        impl AsyncRead for Counting {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<io::Result<()>> {
+                let filled = buf.filled().len();
+                let poll = Pin::new(&mut self.data).poll_read(cx, buf);
+                self.read
+                    .fetch_add(buf.filled().len() - filled, Ordering::SeqCst);
+                poll
+            }
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn prefetch_t_2() -> io::Result<()> {
+            // no more than `depth` chunks are read ahead of the one being processed
+            const CHUNK: usize = 64 * 1024;
+            for depth in [1, 2] {
+                let read = Arc::new(AtomicUsize::new(0));
+                let mut chunks = FileStream::from_reader_without_size(Counting {
+                    data: std::io::Cursor::new(vec![1; 10 * CHUNK]),
+                    read: Arc::clone(&read),
+                })
+                .set_mode(ChunkSize::Bytes(CHUNK))
+                .prefetch(depth);
+                assert_eq!(
+                    chunks.try_next().await?.map(|chunk| chunk.len()),
+                    Some(CHUNK)
+                );
+
+                let started = Instant::now();
+                while read.load(Ordering::SeqCst) < (depth + 1) * CHUNK
+                    && started.elapsed() < Duration::from_secs(5)
+                {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                // the source is read through an 8 KiB buffer
+                let read = read.load(Ordering::SeqCst);
+                assert!(
+                    ((depth + 1) * CHUNK..=(depth + 1) * CHUNK + 8 * 1024).contains(&read),
+                    "{depth}: {read}"
+                );
+            }
+            Ok(())
+        }
+    }
+
+    mod concurrent {
+        use super::*;
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn concurrent_t_0() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(500.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let chunks = FileStream::new(file_orig.path.as_str())
+                .await?
+                .set_mode(ChunkSize::Bytes(16 * 1024))
+                .set_start_position_bytes(1_000)
+                .await?
+                .concurrent(4)
+                .await
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(chunks.len(), 32);
+            assert!(chunks[..31].iter().all(|chunk| chunk.len() == 16 * 1024));
+            // the ranges are yielded in file order
+            assert_eq!(
+                chunks.concat(),
+                std::fs::read(file_orig.path.as_str())?[1_000..]
+            );
+            Ok(())
+        }
+    }
+
+    mod set_strategy {
+        use super::*;
+        use get_chunk::ChunkStrategy;
+
+        /// Always returns the same size, regardless of the read speed
+        struct Constant(f64);
+
+        impl ChunkStrategy for Constant {
+            fn next_chunk_size(&mut self, _: f64, _: f64, _: Option<f64>, _: f64) -> f64 {
+                self.0
+            }
+        }
+
+        #[tokio::test]
+        async fn set_strategy_t_0() -> io::Result<()> {
+            let chunks = FileStream::try_from_data(vec![1; 10_000])
+                .await?
+                .set_strategy(Constant(3_000.0))
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [3_000, 3_000, 3_000, 1_000]
+            );
+            Ok(())
+        }
+    }
+
+    mod set_memory_provider {
+        use super::*;
+        use get_chunk::FixedMemory;
+
+        #[tokio::test]
+        async fn set_memory_provider_t_0() -> io::Result<()> {
+            let chunks = FileStream::try_from_data(vec![1; 30_000])
+                .await?
+                .set_memory_provider(FixedMemory(10_000))
+                .set_mode(ChunkSize::Bytes(100_000))
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            // 85% of the budget
+            assert_eq!(
+                chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+                [8_500, 8_500, 8_500, 4_500]
+            );
+            Ok(())
+        }
+    }
+
+    mod chunk_bounds {
+        use super::*;
+
+        #[tokio::test]
+        async fn chunk_bounds_t_0() -> io::Result<()> {
+            let chunks = FileStream::try_from_data(vec![1; 10 * 1024])
+                .await?
+                .set_min_chunk(IECUnit::new(1.0, IECSize::Kibibyte).into())
+                .set_max_chunk(IECUnit::new(1.0, IECSize::Kibibyte).into())
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [1024; 10]);
+            Ok(())
+        }
+    }
+
+    mod rate_limit {
+        use super::*;
+        use std::time::{Duration, Instant};
+
+        #[tokio::test]
+        async fn rate_limit_t_0() -> io::Result<()> {
+            // 5 chunks of 10 KiB at 100 KiB/s, the reads after the first one wait 100 ms each
+            let data = vec![1; 50 * 1024];
+            let timer = Instant::now();
+            let chunks = FileStream::try_from_data(data.clone())
+                .await?
+                .set_mode(ChunkSize::Bytes(10 * 1024))
+                .set_rate_limit(IECUnit::new(100.0, IECSize::Kibibyte).into())
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert!(timer.elapsed() >= Duration::from_millis(400));
+            assert_eq!(chunks.concat(), data);
+            Ok(())
+        }
+    }
+
+    mod on_progress {
+        use super::*;
+        use get_chunk::Progress;
+        use std::sync::{Arc, Mutex};
+
+        #[tokio::test]
+        async fn on_progress_t_0() -> io::Result<()> {
+            let progress = Arc::new(Mutex::new(Vec::<Progress>::new()));
+            FileStream::try_from_data(vec![1; 5_000])
+                .await?
+                .set_mode(ChunkSize::Bytes(2_000))
+                .on_progress({
+                    let progress = Arc::clone(&progress);
+                    move |value| progress.lock().unwrap().push(value)
+                })
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            let progress = progress.lock().unwrap();
+            assert_eq!(
+                progress
+                    .iter()
+                    .map(|value| value.bytes_read)
+                    .collect::<Vec<_>>(),
+                [2_000, 4_000, 5_000]
+            );
+            assert_eq!(
+                progress
+                    .iter()
+                    .map(|value| value.percent)
+                    .collect::<Vec<_>>(),
+                [Some(40.0), Some(80.0), Some(100.0)]
+            );
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    mod bytes {
+        use super::*;
+
+        #[tokio::test]
+        async fn bytes_t_0() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(300.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let chunks = FileStream::new(file_orig.path.as_str())
+                .await?
+                .set_mode(ChunkSize::Bytes(64 * 1024))
+                .bytes()
+                .collect::<io::Result<Vec<_>>>()
+                .await?;
+
+            assert_eq!(
+                chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(),
+                [65_536, 65_536, 65_536, 65_536, 45_056]
+            );
+            assert_eq!(chunks.concat(), std::fs::read(file_orig.path.as_str())?);
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn bytes_t_1() -> io::Result<()> {
+            let file_orig = FileTest::create_file_with_size(
+                FILE_TEST,
+                IECUnit::new(300.0, IECSize::Kibibyte).into(),
+            )?;
+
+            let mut stream = FileStream::new(file_orig.path.as_str())
+                .await?
+                .set_mode(ChunkSize::Bytes(64 * 1024))
+                .bytes();
+            // every chunk is dropped before the next one is read, so the same allocation is reused,
+            // the memory freed in the meantime is taken so that the allocator cannot hand it back
+            let mut pointers = Vec::new();
+            let mut occupied = Vec::new();
+            while let Some(chunk) = stream.next().await {
+                pointers.push(chunk?.as_ptr());
+                occupied.push(vec![1u8; 64 * 1024]);
+            }
+
+            assert_eq!(pointers.len(), 5);
+            assert!(pointers.iter().all(|pointer| *pointer == pointers[0]));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn send_sync_t_0() {
+        // the stream stays `Send + Sync` with the boxed strategy, memory provider and progress callback
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FileStream<tokio::fs::File>>();
+    }
 }